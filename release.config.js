@@ -43,10 +43,12 @@ const config = {
           "toml set --toml-path tycho-client-py/pyproject.toml project.version ${nextRelease.version}",
           "toml set --toml-path Cargo.toml workspace.dependencies.tycho-common.version ${nextRelease.version}",
           "toml set --toml-path Cargo.toml workspace.dependencies.tycho-client.version ${nextRelease.version}",
+          "toml set --toml-path Cargo.toml workspace.dependencies.tycho-client-derive.version ${nextRelease.version}",
           "toml set --toml-path Cargo.toml workspace.dependencies.tycho-ethereum.version ${nextRelease.version}",
           "toml set --toml-path Cargo.toml workspace.dependencies.tycho-storage.version ${nextRelease.version}",
           "cargo update -p tycho-ethereum",
           "cargo update -p tycho-client",
+          "cargo update -p tycho-client-derive",
           "cargo update -p tycho-common",
           "cargo update -p tycho-indexer",
           "cargo update -p tycho-storage",
@@ -0,0 +1,280 @@
+//! # Tycho Client Derive
+//!
+//! Provides `#[derive(TychoState)]`, which implements `tycho_client::state::TychoState` for
+//! structs with named fields. See the `tycho_client::state` module for the supported field
+//! attributes and decoding rules.
+//!
+//! The generated code refers to `::tycho_client`, so the client crate must be a dependency of the
+//! crate using the derive. It is usually enabled via the `derive` feature of `tycho-client`
+//! instead of depending on this crate directly.
+use std::collections::HashMap;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Field, Fields, GenericArgument, Ident, LitStr,
+    PathArguments, Type,
+};
+
+#[proc_macro_derive(TychoState, attributes(tycho))]
+pub fn derive_tycho_state(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// How a field is populated.
+enum FieldKind {
+    /// Decoded from the attribute of the given name.
+    Attribute { name: String, optional: bool },
+    /// Set to the id of the component.
+    ComponentId,
+    /// Initialised with its `Default` value.
+    Skip,
+}
+
+struct StateField {
+    ident: Ident,
+    ty: Type,
+    kind: FieldKind,
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "TychoState can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "TychoState can only be derived for structs",
+            ))
+        }
+    };
+
+    let fields = fields
+        .iter()
+        .map(parse_field)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut attribute_names: HashMap<&str, &Ident> = HashMap::new();
+    for field in fields.iter() {
+        if let FieldKind::Attribute { name, .. } = &field.kind {
+            if let Some(other) = attribute_names.insert(name, &field.ident) {
+                return Err(Error::new_spanned(
+                    &field.ident,
+                    format!("attribute `{name}` is already mapped to field `{other}`"),
+                ));
+            }
+        }
+    }
+
+    let from_state = fields.iter().map(|field| {
+        let ident = &field.ident;
+        match &field.kind {
+            FieldKind::Attribute { name, optional: false } => quote! {
+                #ident: ::tycho_client::state::required_attribute(
+                    &state.component_id,
+                    &state.attributes,
+                    #name,
+                )?
+            },
+            FieldKind::Attribute { name, optional: true } => quote! {
+                #ident: ::tycho_client::state::optional_attribute(
+                    &state.component_id,
+                    &state.attributes,
+                    #name,
+                )?
+            },
+            FieldKind::ComponentId => quote! {
+                #ident: ::core::convert::From::from(state.component_id.clone())
+            },
+            FieldKind::Skip => quote! {
+                #ident: ::core::default::Default::default()
+            },
+        }
+    });
+
+    // Deltas are decoded into locals first, so a failing delta leaves the state untouched.
+    let attribute_fields: Vec<_> = fields
+        .iter()
+        .filter_map(|field| match &field.kind {
+            FieldKind::Attribute { name, optional } => Some((field, name, *optional)),
+            _ => None,
+        })
+        .collect();
+    // Mixed site hygiene ensures the locals can't clash with identifiers of the user.
+    let locals: Vec<_> = (0..attribute_fields.len())
+        .map(|i| Ident::new(&format!("field_{i}"), Span::mixed_site()))
+        .collect();
+    let declarations = attribute_fields
+        .iter()
+        .zip(locals.iter())
+        .map(|((field, _, _), local)| {
+            let ty = &field.ty;
+            quote! { let mut #local: ::core::option::Option<#ty> = ::core::option::Option::None; }
+        });
+    let updates = attribute_fields
+        .iter()
+        .zip(locals.iter())
+        .map(|((_, name, optional), local)| {
+            let decoded = quote! {
+                ::tycho_client::state::decode_attribute(&delta.component_id, #name, value)?
+            };
+            if *optional {
+                quote! { #name => #local = ::core::option::Option::Some(::core::option::Option::Some(#decoded)), }
+            } else {
+                quote! { #name => #local = ::core::option::Option::Some(#decoded), }
+            }
+        });
+    let deletions = attribute_fields
+        .iter()
+        .zip(locals.iter())
+        .map(|((_, name, optional), local)| {
+            if *optional {
+                quote! {
+                    #name => #local = ::core::option::Option::Some(::core::option::Option::None),
+                }
+            } else {
+                quote! {
+                    #name => {
+                        return ::core::result::Result::Err(
+                            ::tycho_client::state::StateDecodeError::MissingAttribute(
+                                delta.component_id.clone(),
+                                name.clone(),
+                            ),
+                        )
+                    }
+                }
+            }
+        });
+    let assignments = attribute_fields
+        .iter()
+        .zip(locals.iter())
+        .map(|((field, _, _), local)| {
+            let ident = &field.ident;
+            quote! {
+                if let ::core::option::Option::Some(value) = #local {
+                    self.#ident = value;
+                }
+            }
+        });
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::tycho_client::state::TychoState for #ident #ty_generics #where_clause {
+            fn from_state(
+                state: &::tycho_client::state::ResponseProtocolState,
+            ) -> ::core::result::Result<Self, ::tycho_client::state::StateDecodeError> {
+                ::core::result::Result::Ok(Self { #(#from_state,)* })
+            }
+
+            #[allow(unused_variables)]
+            fn apply_delta(
+                &mut self,
+                delta: &::tycho_client::state::ProtocolStateDelta,
+            ) -> ::core::result::Result<(), ::tycho_client::state::StateDecodeError> {
+                #(#declarations)*
+                for (name, value) in delta.updated_attributes.iter() {
+                    match name.as_str() {
+                        #(#updates)*
+                        _ => {}
+                    }
+                }
+                for name in delta.deleted_attributes.iter() {
+                    match name.as_str() {
+                        #(#deletions)*
+                        _ => {}
+                    }
+                }
+                #(#assignments)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    })
+}
+
+fn parse_field(field: &Field) -> Result<StateField, Error> {
+    let ident = field
+        .ident
+        .clone()
+        .expect("named fields have an ident");
+    let mut rename: Option<String> = None;
+    let mut component_id = false;
+    let mut skip = false;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("tycho"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let name: LitStr = meta.value()?.parse()?;
+                rename = Some(name.value());
+                Ok(())
+            } else if meta.path.is_ident("component_id") {
+                component_id = true;
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `rename = \"...\"`, `component_id` or `skip`"))
+            }
+        })?;
+    }
+
+    let kind = match (rename, component_id, skip) {
+        (None, false, false) => FieldKind::Attribute {
+            name: ident
+                .to_string()
+                .trim_start_matches("r#")
+                .to_string(),
+            optional: option_inner_type(&field.ty).is_some(),
+        },
+        (Some(name), false, false) => {
+            FieldKind::Attribute { name, optional: option_inner_type(&field.ty).is_some() }
+        }
+        (None, true, false) => FieldKind::ComponentId,
+        (None, false, true) => FieldKind::Skip,
+        _ => {
+            return Err(Error::new_spanned(
+                &ident,
+                "`rename`, `component_id` and `skip` are mutually exclusive",
+            ))
+        }
+    };
+
+    Ok(StateField { ident, ty: field.ty.clone(), kind })
+}
+
+/// Returns `T` if `ty` is an `Option<T>`.
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    if path.qself.is_some() {
+        return None;
+    }
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match args.args.first()? {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
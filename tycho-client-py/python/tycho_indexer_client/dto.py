@@ -142,6 +142,8 @@ class ProtocolComponent(BaseModel):
     change: ChangeType
     creation_tx: HexBytes
     created_at: datetime
+    manifest_uri: Optional[str] = None
+    factory: Optional[HexBytes] = None
 
 
 class ResponseToken(BaseModel):
@@ -0,0 +1,258 @@
+//! Load test for the websocket broadcast path of a (local) Tycho indexer.
+//!
+//! Spawns N subscribers, each with its own websocket connection, and lets them consume deltas for
+//! a fixed duration. To simulate varying filters, subscriber `i` subscribes to a different
+//! combination of the given extractors and every other combination requests state.
+//!
+//! Reports the fan-out latency of each block, i.e. how long after the first subscriber a
+//! subscriber received the block, and the peak memory of the benchmark and optionally of the
+//! indexer process (Linux only).
+//!
+//! To run: cargo run --release --example ws-bench -- -e uniswap_v2 -e uniswap_v3 \
+//!     --indexer-pid $(pgrep tycho-indexer)
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use futures03::{stream, StreamExt};
+use tokio::sync::mpsc;
+use tycho_client::{
+    deltas::{DeltasClient, SubscriptionOptions},
+    WsDeltasClient,
+};
+use tycho_common::{
+    dto::{Chain, ExtractorIdentity},
+    Bytes,
+};
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// Tycho server URL, without protocol.
+    #[clap(long, default_value = "localhost:4242", env = "TYCHO_URL")]
+    tycho_url: String,
+
+    /// Tycho gateway API key. If set, a secure websocket connection is used.
+    #[clap(short = 'k', long, env = "TYCHO_AUTH_TOKEN")]
+    auth_key: Option<String>,
+
+    /// The blockchain of the extractors.
+    #[clap(short = 'c', long, default_value = "ethereum")]
+    chain: String,
+
+    /// Extractors to subscribe to, can be given multiple times.
+    #[clap(short = 'e', long, number_of_values = 1, required = true)]
+    exchange: Vec<String>,
+
+    /// Number of simulated subscribers.
+    #[clap(short = 'n', long, default_value = "1000")]
+    subscribers: usize,
+
+    /// Maximum number of subscribers connecting at the same time.
+    #[clap(long, default_value = "50")]
+    connect_concurrency: usize,
+
+    /// Duration of the measurement in seconds, starting once all subscribers are connected.
+    #[clap(long, default_value = "60")]
+    duration: u64,
+
+    /// Process id of the indexer, used to sample its memory.
+    #[clap(long)]
+    indexer_pid: Option<u32>,
+}
+
+/// A block received by a subscriber.
+struct Receipt {
+    extractor: String,
+    block_hash: Bytes,
+    received_at: Instant,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let chain =
+        Chain::from_str(&args.chain).map_err(|_| anyhow!("Unknown chain: {}", args.chain))?;
+    let ws_url = match args.auth_key {
+        Some(_) => format!("wss://{}", args.tycho_url),
+        None => format!("ws://{}", args.tycho_url),
+    };
+
+    let (receipt_tx, mut receipt_rx) = mpsc::unbounded_channel();
+    let connect_start = Instant::now();
+    let connected: Vec<_> = stream::iter(0..args.subscribers)
+        .map(|i| {
+            let extractors = extractors_of_subscriber(i, &args.exchange)
+                .into_iter()
+                .map(|name| ExtractorIdentity::new(chain, name))
+                .collect();
+            let options = SubscriptionOptions::new().with_state(i % 2 == 0);
+            start_subscriber(
+                &ws_url,
+                args.auth_key.as_deref(),
+                extractors,
+                options,
+                receipt_tx.clone(),
+            )
+        })
+        .buffer_unordered(args.connect_concurrency)
+        .collect()
+        .await;
+    drop(receipt_tx);
+    let (clients, errors): (Vec<_>, Vec<_>) = connected
+        .into_iter()
+        .partition(Result::is_ok);
+    println!(
+        "connected {} of {} subscribers in {:?}",
+        clients.len(),
+        args.subscribers,
+        connect_start.elapsed()
+    );
+    if let Some(Err(err)) = errors.first() {
+        println!("first connection error: {err}");
+    }
+
+    let mut receipts: HashMap<(String, Bytes), Vec<Instant>> = HashMap::new();
+    let mut memory = MemoryStats::default();
+    memory.sample(args.indexer_pid);
+    let deadline = tokio::time::sleep(Duration::from_secs(args.duration));
+    tokio::pin!(deadline);
+    let mut sample_interval = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        tokio::select! {
+            _ = &mut deadline => break,
+            _ = sample_interval.tick() => memory.sample(args.indexer_pid),
+            receipt = receipt_rx.recv() => match receipt {
+                Some(receipt) => receipts
+                    .entry((receipt.extractor, receipt.block_hash))
+                    .or_default()
+                    .push(receipt.received_at),
+                None => break,
+            },
+        }
+    }
+
+    for client in clients.into_iter().flatten() {
+        let _ = client.close().await;
+    }
+
+    print_report(&receipts, &memory);
+    Ok(())
+}
+
+/// Returns the extractors subscriber `i` subscribes to.
+///
+/// Subscribers cycle through all non-empty combinations of the extractors.
+fn extractors_of_subscriber(i: usize, extractors: &[String]) -> Vec<&str> {
+    let combinations = (1usize << extractors.len().min(16)) - 1;
+    let mask = i % combinations + 1;
+    extractors
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| mask & (1 << j) != 0)
+        .map(|(_, name)| name.as_str())
+        .collect()
+}
+
+/// Connects a subscriber and forwards a receipt for every block it receives.
+async fn start_subscriber(
+    ws_url: &str,
+    auth_key: Option<&str>,
+    extractors: Vec<ExtractorIdentity>,
+    options: SubscriptionOptions,
+    receipt_tx: mpsc::UnboundedSender<Receipt>,
+) -> Result<WsDeltasClient> {
+    let client = WsDeltasClient::new(ws_url, auth_key)?;
+    client.connect().await?;
+    for extractor in extractors {
+        let (_, mut rx) = client
+            .subscribe(extractor, options.clone())
+            .await?;
+        let receipt_tx = receipt_tx.clone();
+        tokio::spawn(async move {
+            while let Some(msg) = rx.recv().await {
+                let receipt = Receipt {
+                    extractor: msg.extractor,
+                    block_hash: msg.block.hash,
+                    received_at: Instant::now(),
+                };
+                if receipt_tx.send(receipt).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    Ok(client)
+}
+
+#[derive(Default)]
+struct MemoryStats {
+    peak_bench_kib: Option<u64>,
+    peak_indexer_kib: Option<u64>,
+}
+
+impl MemoryStats {
+    fn sample(&mut self, indexer_pid: Option<u32>) {
+        self.peak_bench_kib = self
+            .peak_bench_kib
+            .max(rss_kib("/proc/self/status"));
+        if let Some(pid) = indexer_pid {
+            self.peak_indexer_kib = self
+                .peak_indexer_kib
+                .max(rss_kib(&format!("/proc/{pid}/status")));
+        }
+    }
+}
+
+/// Reads the resident set size from a procfs status file. Returns `None` if it is not available,
+/// e.g. on non Linux systems.
+fn rss_kib(status_path: &str) -> Option<u64> {
+    std::fs::read_to_string(status_path)
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn print_report(receipts: &HashMap<(String, Bytes), Vec<Instant>>, memory: &MemoryStats) {
+    let mut latencies: Vec<Duration> = receipts
+        .values()
+        .flat_map(|received| {
+            let first = received
+                .iter()
+                .min()
+                .copied()
+                .expect("receipts are never empty");
+            received
+                .iter()
+                .map(move |t| t.duration_since(first))
+        })
+        .collect();
+    latencies.sort();
+
+    println!("blocks: {}", receipts.len());
+    println!("messages: {}", latencies.len());
+    if latencies.is_empty() {
+        println!("fan-out latency: no messages received");
+    } else {
+        println!("fan-out latency:");
+        for p in [0.5, 0.9, 0.99, 0.999, 1.0] {
+            // nearest-rank percentile
+            let idx = ((p * latencies.len() as f64).ceil() as usize).max(1) - 1;
+            println!("  p{:<6} {:?}", p * 100.0, latencies[idx]);
+        }
+    }
+    let format_kib = |kib: Option<u64>| {
+        kib.map(|kib| format!("{:.1} MiB", kib as f64 / 1024.0))
+            .unwrap_or_else(|| "n/a".to_string())
+    };
+    println!("peak memory benchmark: {}", format_kib(memory.peak_bench_kib));
+    println!("peak memory indexer: {}", format_kib(memory.peak_indexer_kib));
+}
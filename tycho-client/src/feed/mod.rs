@@ -33,6 +33,7 @@ use crate::feed::{
 
 mod block_history;
 pub mod component_tracker;
+pub mod progress;
 pub mod synchronizer;
 
 /// A trait representing a minimal interface for types that behave like a block header.
@@ -0,0 +1,189 @@
+//! Progress reporting for protocol state synchronization.
+//!
+//! Retrieving the initial snapshot of a large protocol can take several minutes. Synchronizers
+//! can optionally be given a [`SyncProgressSender`] through which they report which stage of the
+//! startup they are in, how many snapshots were retrieved so far and an estimate of the remaining
+//! time. Applications can use these events to render startup progress or to detect stalls, e.g.
+//! by alerting if no event was received for a while.
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc::{error::TrySendError, Sender};
+use tracing::warn;
+use tycho_common::dto::ExtractorIdentity;
+
+/// Events emitted by a synchronizer while it retrieves protocol components and snapshots.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyncProgress {
+    /// The components to be tracked were retrieved from the server.
+    ComponentsDiscovered {
+        extractor_id: ExtractorIdentity,
+        n_components: usize,
+        n_contracts: usize,
+    },
+    /// A batch of snapshots was retrieved.
+    SnapshotProgress(SnapshotProgress),
+    /// All requested snapshots for a block were retrieved.
+    SnapshotCompleted(SnapshotProgress),
+}
+
+/// Progress of a single snapshot retrieval.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotProgress {
+    pub extractor_id: ExtractorIdentity,
+    /// The block the snapshot is retrieved at.
+    pub block_number: u64,
+    pub components_fetched: usize,
+    pub components_total: usize,
+    pub contracts_fetched: usize,
+    pub contracts_total: usize,
+    /// Size of the snapshot response bodies received from the server.
+    pub bytes_downloaded: usize,
+    pub elapsed: Duration,
+    /// Estimated time until the snapshot is complete, extrapolated from the progress so far.
+    /// `None` until the first batch was retrieved.
+    pub eta: Option<Duration>,
+}
+
+impl SnapshotProgress {
+    /// Fraction of entities (components and contracts) retrieved so far.
+    pub fn fraction(&self) -> f64 {
+        let total = self.components_total + self.contracts_total;
+        if total == 0 {
+            return 1.0;
+        }
+        (self.components_fetched + self.contracts_fetched) as f64 / total as f64
+    }
+}
+
+/// Sending half for progress events.
+///
+/// Events are sent without waiting, if the receiver lags behind events are dropped instead of
+/// slowing down the synchronization.
+#[derive(Clone, Debug)]
+pub struct SyncProgressSender(Sender<SyncProgress>);
+
+impl SyncProgressSender {
+    pub fn new(tx: Sender<SyncProgress>) -> Self {
+        Self(tx)
+    }
+
+    pub(crate) fn send(&self, event: SyncProgress) {
+        match self.0.try_send(event) {
+            Ok(()) | Err(TrySendError::Closed(_)) => (),
+            Err(TrySendError::Full(_)) => {
+                warn!("Progress receiver is lagging behind, dropping progress event");
+            }
+        }
+    }
+}
+
+/// Tracks the progress of a snapshot retrieval and reports it to an optional progress sender.
+pub(crate) struct SnapshotProgressTracker<'a> {
+    sender: Option<&'a SyncProgressSender>,
+    started: Instant,
+    progress: SnapshotProgress,
+}
+
+impl<'a> SnapshotProgressTracker<'a> {
+    pub(crate) fn new(
+        sender: Option<&'a SyncProgressSender>,
+        extractor_id: ExtractorIdentity,
+        block_number: u64,
+        components_total: usize,
+        contracts_total: usize,
+    ) -> Self {
+        Self {
+            sender,
+            started: Instant::now(),
+            progress: SnapshotProgress {
+                extractor_id,
+                block_number,
+                components_fetched: 0,
+                components_total,
+                contracts_fetched: 0,
+                contracts_total,
+                bytes_downloaded: 0,
+                elapsed: Duration::ZERO,
+                eta: None,
+            },
+        }
+    }
+
+    /// Records a retrieved batch of snapshots and emits a progress event.
+    pub(crate) fn record(&mut self, components: usize, contracts: usize, bytes: usize) {
+        let Some(sender) = self.sender else {
+            return;
+        };
+        self.progress.components_fetched += components;
+        self.progress.contracts_fetched += contracts;
+        self.progress.bytes_downloaded += bytes;
+        self.progress.elapsed = self.started.elapsed();
+        let fraction = self.progress.fraction();
+        self.progress.eta = (fraction > 0.0).then(|| {
+            self.progress
+                .elapsed
+                .mul_f64((1.0 - fraction) / fraction)
+        });
+        sender.send(SyncProgress::SnapshotProgress(self.progress.clone()));
+    }
+
+    /// Emits the final progress event for this snapshot.
+    pub(crate) fn complete(mut self) {
+        let Some(sender) = self.sender else {
+            return;
+        };
+        self.progress.elapsed = self.started.elapsed();
+        self.progress.eta = Some(Duration::ZERO);
+        sender.send(SyncProgress::SnapshotCompleted(self.progress));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::sync::mpsc::channel;
+    use tycho_common::dto::Chain;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_snapshot_progress_tracker() {
+        let (tx, mut rx) = channel(10);
+        let sender = SyncProgressSender::new(tx);
+        let extractor_id = ExtractorIdentity::new(Chain::Ethereum, "uniswap-v2");
+        let mut tracker = SnapshotProgressTracker::new(Some(&sender), extractor_id, 1, 3, 1);
+
+        tracker.record(2, 0, 100);
+        tracker.record(1, 1, 50);
+        tracker.complete();
+
+        let SyncProgress::SnapshotProgress(first) = rx.recv().await.unwrap() else {
+            panic!("Expected snapshot progress event");
+        };
+        assert_eq!(first.components_fetched, 2);
+        assert_eq!(first.bytes_downloaded, 100);
+        assert_eq!(first.fraction(), 0.5);
+        assert!(first.eta.is_some());
+
+        let SyncProgress::SnapshotProgress(second) = rx.recv().await.unwrap() else {
+            panic!("Expected snapshot progress event");
+        };
+        assert_eq!(second.fraction(), 1.0);
+        assert_eq!(second.eta, Some(Duration::ZERO));
+
+        let SyncProgress::SnapshotCompleted(done) = rx.recv().await.unwrap() else {
+            panic!("Expected snapshot completed event");
+        };
+        assert_eq!(done.bytes_downloaded, 150);
+        assert_eq!(done.contracts_fetched, 1);
+    }
+
+    #[test]
+    fn test_snapshot_progress_tracker_disabled() {
+        let extractor_id = ExtractorIdentity::new(Chain::Ethereum, "uniswap-v2");
+        let mut tracker = SnapshotProgressTracker::new(None, extractor_id, 1, 3, 0);
+
+        tracker.record(3, 0, 100);
+
+        assert_eq!(tracker.progress.components_fetched, 0);
+    }
+}
@@ -15,20 +15,21 @@ use tokio::{
 use tracing::{debug, error, info, instrument, trace, warn};
 use tycho_common::{
     dto::{
-        BlockChanges, BlockParam, Chain, ComponentTvlRequestBody, EntryPointWithTracingParams,
+        BlockChanges, BlockParam, ComponentTvlRequestBody, EntryPointWithTracingParams,
         ExtractorIdentity, ProtocolComponent, ResponseAccount, ResponseProtocolState,
         TracingResult, VersionParam,
     },
-    Bytes,
+    models, Bytes,
 };
 
 use crate::{
     deltas::{DeltasClient, SubscriptionOptions},
     feed::{
         component_tracker::{ComponentFilter, ComponentTracker},
+        progress::{SnapshotProgressTracker, SyncProgress, SyncProgressSender},
         BlockHeader, HeaderLike,
     },
-    rpc::{RPCClient, RPCError},
+    rpc::{count_received_bytes, RPCClient, RPCError},
     DeltasError,
 };
 
@@ -87,8 +88,13 @@ pub struct ProtocolStateSynchronizer<R: RPCClient, D: DeltasClient> {
     last_synced_block: Option<BlockHeader>,
     timeout: u64,
     include_tvl: bool,
+    progress_tx: Option<SyncProgressSender>,
 }
 
+/// Number of components or contracts retrieved per snapshot batch. Progress is reported after
+/// each batch, the batch itself is retrieved with multiple concurrent requests.
+const SNAPSHOT_BATCH_SIZE: usize = 400;
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct ComponentWithState {
     pub state: ResponseProtocolState,
@@ -129,7 +135,8 @@ where
     pub snapshots: Snapshot,
     /// A single delta contains state updates for all tracked components, as well as additional
     /// information about the system components e.g. newly added components (even below tvl), tvl
-    /// updates, balance updates.
+    /// updates, balance updates. Updates of accounts shared with other extractors may lag
+    /// behind by one or more blocks, see [`BlockChanges::account_updates`].
     pub deltas: Option<BlockChanges>,
     /// Components that stopped being tracked.
     pub removed_components: HashMap<String, ProtocolComponent>,
@@ -243,9 +250,16 @@ where
             last_synced_block: None,
             timeout,
             include_tvl,
+            progress_tx: None,
         }
     }
 
+    /// Reports progress of component discovery and snapshot retrieval to the given sender.
+    pub fn with_progress_sender(mut self, progress_tx: SyncProgressSender) -> Self {
+        self.progress_tx = Some(progress_tx);
+        self
+    }
+
     /// Retrieves state snapshots of the requested components
     #[allow(deprecated)]
     async fn get_snapshots<'a, I: IntoIterator<Item = &'a String>>(
@@ -292,7 +306,10 @@ where
 
         //TODO: Improve this, we should not query for every component, but only for the ones that
         // could have entrypoints. Maybe apply a filter per protocol?
-        let entrypoints_result = if self.extractor_id.chain == Chain::Ethereum {
+        let entrypoints_result = if models::Chain::from(self.extractor_id.chain)
+            .spec()
+            .supports_entrypoint_tracing
+        {
             // Fetch entrypoints
             let result = self
                 .rpc_client
@@ -311,23 +328,42 @@ where
             None
         };
 
+        // Contracts are known upfront, so the progress accounts for them from the start
+        let contract_ids = self
+            .component_tracker
+            .get_contracts_by_component(&component_ids);
+        let mut progress = SnapshotProgressTracker::new(
+            self.progress_tx.as_ref(),
+            self.extractor_id.clone(),
+            header.number,
+            component_ids.len(),
+            contract_ids.len(),
+        );
+
         // Fetch protocol states
-        let mut protocol_states = self
-            .rpc_client
-            .get_protocol_states_paginated(
-                self.extractor_id.chain,
-                &component_ids,
-                &self.extractor_id.name,
-                self.retrieve_balances,
-                &version,
-                100,
-                4,
+        let mut protocol_states = HashMap::with_capacity(component_ids.len());
+        for batch in component_ids.chunks(SNAPSHOT_BATCH_SIZE) {
+            let (response, n_bytes) = count_received_bytes(
+                self.rpc_client
+                    .get_protocol_states_paginated(
+                        self.extractor_id.chain,
+                        batch,
+                        &self.extractor_id.name,
+                        self.retrieve_balances,
+                        &version,
+                        100,
+                        4,
+                    ),
             )
-            .await?
-            .states
-            .into_iter()
-            .map(|state| (state.component_id.clone(), state))
-            .collect::<HashMap<_, _>>();
+            .await;
+            let states = response?.states;
+            progress.record(batch.len(), 0, n_bytes);
+            protocol_states.extend(
+                states
+                    .into_iter()
+                    .map(|state| (state.component_id.clone(), state)),
+            );
+        }
 
         trace!(states=?&protocol_states, "Retrieved ProtocolStates");
         let states = self
@@ -367,29 +403,33 @@ where
             .collect();
 
         // Fetch contract states
-        let contract_ids = self
-            .component_tracker
-            .get_contracts_by_component(&component_ids);
         let vm_storage = if !contract_ids.is_empty() {
             let ids: Vec<Bytes> = contract_ids
                 .clone()
                 .into_iter()
                 .collect();
-            let contract_states = self
-                .rpc_client
-                .get_contract_state_paginated(
-                    self.extractor_id.chain,
-                    ids.as_slice(),
-                    &self.extractor_id.name,
-                    &version,
-                    100,
-                    4,
+            let mut contract_states = HashMap::with_capacity(ids.len());
+            for batch in ids.chunks(SNAPSHOT_BATCH_SIZE) {
+                let (response, n_bytes) = count_received_bytes(
+                    self.rpc_client
+                        .get_contract_state_paginated(
+                            self.extractor_id.chain,
+                            batch,
+                            &self.extractor_id.name,
+                            &version,
+                            100,
+                            4,
+                        ),
                 )
-                .await?
-                .accounts
-                .into_iter()
-                .map(|acc| (acc.address.clone(), acc))
-                .collect::<HashMap<_, _>>();
+                .await;
+                let accounts = response?.accounts;
+                progress.record(0, batch.len(), n_bytes);
+                contract_states.extend(
+                    accounts
+                        .into_iter()
+                        .map(|acc| (acc.address.clone(), acc)),
+                );
+            }
 
             trace!(states=?&contract_states, "Retrieved ContractState");
 
@@ -436,6 +476,8 @@ where
             HashMap::new()
         };
 
+        progress.complete();
+
         Ok(StateSyncMessage {
             header,
             snapshots: Snapshot { states, vm_storage },
@@ -641,11 +683,16 @@ where
         self.component_tracker
             .initialise_components()
             .await?;
-        info!(
-            n_components = self.component_tracker.components.len(),
-            n_contracts = self.component_tracker.contracts.len(),
-            "Finished retrieving components",
-        );
+        let n_components = self.component_tracker.components.len();
+        let n_contracts = self.component_tracker.contracts.len();
+        info!(n_components, n_contracts, "Finished retrieving components");
+        if let Some(progress_tx) = &self.progress_tx {
+            progress_tx.send(SyncProgress::ComponentsDiscovered {
+                extractor_id: self.extractor_id.clone(),
+                n_components,
+                n_contracts,
+            });
+        }
 
         Ok(())
     }
@@ -733,7 +780,8 @@ mod test {
 
     use test_log::test;
     use tycho_common::dto::{
-        Block, Chain, ComponentTvlRequestBody, ComponentTvlRequestResponse, DCIUpdate, EntryPoint,
+        Block, Chain, ComponentFeesRequestBody, ComponentFeesRequestResponse,
+        ComponentTvlRequestBody, ComponentTvlRequestResponse, DCIUpdate, EntryPoint,
         PaginationResponse, ProtocolComponentRequestResponse, ProtocolComponentsRequestBody,
         ProtocolStateRequestBody, ProtocolStateRequestResponse, ProtocolSystemsRequestBody,
         ProtocolSystemsRequestResponse, RPCTracerParams, StateRequestBody, StateRequestResponse,
@@ -808,6 +856,13 @@ mod test {
             self.0.get_component_tvl(request).await
         }
 
+        async fn get_component_fees(
+            &self,
+            request: &ComponentFeesRequestBody,
+        ) -> Result<ComponentFeesRequestResponse, RPCError> {
+            self.0.get_component_fees(request).await
+        }
+
         async fn get_traced_entry_points(
             &self,
             request: &TracedEntryPointRequestBody,
@@ -952,6 +1007,61 @@ mod test {
         assert_eq!(snap, exp);
     }
 
+    #[test(tokio::test)]
+    async fn test_get_snapshots_reports_progress() {
+        let header = BlockHeader { number: 1, ..Default::default() };
+        let mut rpc = MockRPCClient::new();
+        rpc.expect_get_protocol_states()
+            .returning(|_| Ok(state_snapshot_native()));
+        rpc.expect_get_contract_state()
+            .returning(|_| Ok(state_snapshot_vm()));
+        rpc.expect_get_traced_entry_points()
+            .returning(|_| {
+                Ok(TracedEntryPointRequestResponse {
+                    traced_entry_points: HashMap::new(),
+                    pagination: PaginationResponse::new(0, 20, 0),
+                })
+            });
+        let (progress_tx, mut progress_rx) = channel(10);
+        let mut state_sync = with_mocked_clients(false, false, Some(rpc), None)
+            .with_progress_sender(SyncProgressSender::new(progress_tx));
+        let component = ProtocolComponent {
+            id: "Component1".to_string(),
+            contract_ids: vec![Bytes::from("0x0badc0ffee"), Bytes::from("0xbabe42")],
+            ..Default::default()
+        };
+        state_sync
+            .component_tracker
+            .components
+            .insert("Component1".to_string(), component);
+        let components_arg = ["Component1".to_string()];
+
+        state_sync
+            .get_snapshots(header, Some(&components_arg))
+            .await
+            .expect("Retrieving snapshot failed");
+
+        // contracts are accounted for before their states are retrieved
+        let Some(SyncProgress::SnapshotProgress(states)) = progress_rx.recv().await else {
+            panic!("Expected snapshot progress event");
+        };
+        assert_eq!(states.block_number, 1);
+        assert_eq!(states.components_fetched, 1);
+        assert_eq!(states.components_total, 1);
+        assert_eq!(states.contracts_fetched, 0);
+        assert_eq!(states.contracts_total, 2);
+        assert!(states.fraction() < 1.0);
+        let Some(SyncProgress::SnapshotProgress(contracts)) = progress_rx.recv().await else {
+            panic!("Expected snapshot progress event");
+        };
+        assert_eq!(contracts.contracts_fetched, 2);
+        assert_eq!(contracts.fraction(), 1.0);
+        let Some(SyncProgress::SnapshotCompleted(completed)) = progress_rx.recv().await else {
+            panic!("Expected snapshot completed event");
+        };
+        assert_eq!(completed.fraction(), 1.0);
+    }
+
     #[test(tokio::test)]
     async fn test_get_snapshots_native_with_tvl() {
         let header = BlockHeader::default();
@@ -1929,6 +2039,8 @@ mod test {
                             creation_tx: Default::default(),
                             created_at: Default::default(),
                             change: Default::default(),
+                            manifest_uri: None,
+                            factory: None,
                         },
                     )]
                     .into_iter()
@@ -23,17 +23,23 @@
 //!
 //! - `rpc` module provides utilities for retrieving snapshots, and associated data such as tokens.
 //! - `updates` module handles receiving and processing updates messages from the server.
+//! - `state` module decodes protocol states into user defined structs.
 const TYCHO_SERVER_VERSION: &str = "v1";
 
 pub mod cli;
 pub mod deltas;
 pub mod feed;
 pub mod rpc;
+pub mod state;
 pub mod stream;
 
 #[cfg(test)]
 #[macro_use]
 extern crate pretty_assertions;
 
+// Allows code derived by `tycho-client-derive` to refer to `::tycho_client` within this crate.
+#[cfg(test)]
+extern crate self as tycho_client;
+
 pub use deltas::{DeltasError, WsDeltasClient};
 pub use rpc::{HttpRPCClient, RPCError};
@@ -3,7 +3,7 @@
 //! The objective of this module is to provide swift and simplified access to the Remote Procedure
 //! Call (RPC) endpoints of Tycho. These endpoints are chiefly responsible for facilitating data
 //! queries, especially querying snapshots of data.
-use std::{collections::HashMap, sync::Arc};
+use std::{cell::Cell, collections::HashMap, future::Future, sync::Arc};
 
 use async_trait::async_trait;
 use futures03::future::try_join_all;
@@ -15,18 +15,40 @@ use tokio::sync::Semaphore;
 use tracing::{debug, error, instrument, trace, warn};
 use tycho_common::{
     dto::{
-        Chain, ComponentTvlRequestBody, ComponentTvlRequestResponse, PaginationParams,
-        PaginationResponse, ProtocolComponentRequestResponse, ProtocolComponentsRequestBody,
-        ProtocolStateRequestBody, ProtocolStateRequestResponse, ProtocolSystemsRequestBody,
-        ProtocolSystemsRequestResponse, ResponseToken, StateRequestBody, StateRequestResponse,
-        TokensRequestBody, TokensRequestResponse, TracedEntryPointRequestBody,
-        TracedEntryPointRequestResponse, VersionParam,
+        Chain, ComponentFeesRequestBody, ComponentFeesRequestResponse, ComponentTvlRequestBody,
+        ComponentTvlRequestResponse, PaginationParams, PaginationResponse,
+        ProtocolComponentRequestResponse, ProtocolComponentsRequestBody, ProtocolStateRequestBody,
+        ProtocolStateRequestResponse, ProtocolSystemsRequestBody, ProtocolSystemsRequestResponse,
+        ResponseToken, StateRequestBody, StateRequestResponse, TokensRequestBody,
+        TokensRequestResponse, TracedEntryPointRequestBody, TracedEntryPointRequestResponse,
+        VersionParam,
     },
     Bytes,
 };
 
 use crate::TYCHO_SERVER_VERSION;
 
+tokio::task_local! {
+    /// Size of the response bodies received within [`count_received_bytes`].
+    static RECEIVED_BYTES: Cell<usize>;
+}
+
+/// Runs `fut` and returns its output together with the size of the snapshot response bodies the
+/// [`HttpRPCClient`] received while running it. Requests made by other tasks are not counted.
+pub(crate) async fn count_received_bytes<F: Future>(fut: F) -> (F::Output, usize) {
+    RECEIVED_BYTES
+        .scope(Cell::new(0), async move {
+            let output = fut.await;
+            (output, RECEIVED_BYTES.with(Cell::get))
+        })
+        .await
+}
+
+fn record_received_bytes(n_bytes: usize) {
+    // outside of `count_received_bytes` nobody is interested in the size
+    let _ = RECEIVED_BYTES.try_with(|received| received.set(received.get() + n_bytes));
+}
+
 #[derive(Error, Debug)]
 pub enum RPCError {
     /// The passed tycho url failed to parse.
@@ -387,6 +409,12 @@ pub trait RPCClient: Send + Sync {
         request: &ComponentTvlRequestBody,
     ) -> Result<ComponentTvlRequestResponse, RPCError>;
 
+    /// Retrieves the daily fees accrued by protocol components.
+    async fn get_component_fees(
+        &self,
+        request: &ComponentFeesRequestBody,
+    ) -> Result<ComponentFeesRequestResponse, RPCError>;
+
     async fn get_component_tvl_paginated(
         &self,
         request: &ComponentTvlRequestBody,
@@ -647,6 +675,7 @@ impl RPCClient for HttpRPCClient {
             .text()
             .await
             .map_err(|e| RPCError::ParseResponse(e.to_string()))?;
+        record_received_bytes(body.len());
         if body.is_empty() {
             // Pure native protocols will return empty contract states
             return Ok(StateRequestResponse {
@@ -738,6 +767,7 @@ impl RPCClient for HttpRPCClient {
             .text()
             .await
             .map_err(|e| RPCError::ParseResponse(e.to_string()))?;
+        record_received_bytes(body.len());
 
         if body.is_empty() {
             // Pure VM protocols will return empty states
@@ -854,6 +884,40 @@ impl RPCClient for HttpRPCClient {
         Ok(component_tvl)
     }
 
+    async fn get_component_fees(
+        &self,
+        request: &ComponentFeesRequestBody,
+    ) -> Result<ComponentFeesRequestResponse, RPCError> {
+        let uri = format!(
+            "{}/{}/component_fees",
+            self.url
+                .to_string()
+                .trim_end_matches('/'),
+            TYCHO_SERVER_VERSION
+        );
+        debug!(%uri, "Sending get_component_fees request to Tycho server");
+        trace!(?request, "Sending request to Tycho server");
+        let response = self
+            .http_client
+            .post(&uri)
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| RPCError::HttpClient(e.to_string()))?;
+        trace!(?response, "Received response from Tycho server");
+        let body = response
+            .text()
+            .await
+            .map_err(|e| RPCError::ParseResponse(e.to_string()))?;
+        let component_fees =
+            serde_json::from_str::<ComponentFeesRequestResponse>(&body).map_err(|err| {
+                error!("Failed to parse component_fees response: {:?}", &body);
+                RPCError::ParseResponse(format!("Error: {err}, Body: {body}"))
+            })?;
+        trace!(?component_fees, "Received component_fees response from Tycho server");
+        Ok(component_fees)
+    }
+
     async fn get_traced_entry_points(
         &self,
         request: &TracedEntryPointRequestBody,
@@ -1165,6 +1229,33 @@ mod tests {
         assert_eq!(states[0].balances, expected_balances);
     }
 
+    #[tokio::test]
+    async fn test_count_received_bytes() {
+        let mut server = Server::new_async().await;
+        let server_resp = r#"{"states":[],"pagination":{"page":0,"page_size":20,"total":0}}"#;
+        let mocked_server = server
+            .mock("POST", "/v1/protocol_state")
+            .expect(2)
+            .with_body(server_resp)
+            .create_async()
+            .await;
+        let client = HttpRPCClient::new(server.url().as_str(), None).expect("create client");
+
+        let (response, n_bytes) = count_received_bytes(async {
+            client
+                .get_protocol_states(&Default::default())
+                .await?;
+            client
+                .get_protocol_states(&Default::default())
+                .await
+        })
+        .await;
+
+        mocked_server.assert();
+        response.expect("get state");
+        assert_eq!(n_bytes, 2 * server_resp.len());
+    }
+
     #[tokio::test]
     async fn test_get_tokens() {
         let mut server = Server::new_async().await;
@@ -1316,6 +1407,52 @@ mod tests {
         assert_eq!(component_tvl.get("component1"), Some(&100.0));
     }
 
+    #[tokio::test]
+    async fn test_get_component_fees() {
+        let mut server = Server::new_async().await;
+        let server_resp = r#"
+        {
+            "fees": [
+                {
+                    "component_id": "component1",
+                    "token": "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+                    "day": "2025-08-04",
+                    "lp_fees": "0x000000000000000000000000000000000000000000000000000000000000012c",
+                    "lp_fees_float": 300.0,
+                    "protocol_fees": "0x000000000000000000000000000000000000000000000000000000000000001e",
+                    "protocol_fees_float": 30.0
+                }
+            ],
+            "pagination": {
+                "page": 0,
+                "page_size": 20,
+                "total": 1
+            }
+        }
+        "#;
+        // test that the response is deserialized correctly
+        serde_json::from_str::<ComponentFeesRequestResponse>(server_resp).expect("deserialize");
+
+        let mocked_server = server
+            .mock("POST", "/v1/component_fees")
+            .expect(1)
+            .with_body(server_resp)
+            .create_async()
+            .await;
+        let client = HttpRPCClient::new(server.url().as_str(), None).expect("create client");
+
+        let response = client
+            .get_component_fees(&Default::default())
+            .await
+            .expect("get component fees");
+
+        mocked_server.assert();
+        assert_eq!(response.fees.len(), 1);
+        assert_eq!(response.fees[0].component_id, "component1");
+        assert_eq!(response.fees[0].lp_fees, Bytes::from(300u64).lpad(32, 0));
+        assert_eq!(response.fees[0].lp_fees_float, 300.0);
+    }
+
     #[tokio::test]
     async fn test_get_traced_entry_points() {
         let mut server = Server::new_async().await;
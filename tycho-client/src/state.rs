@@ -0,0 +1,313 @@
+//! # Typed Protocol States
+//!
+//! Protocol states are transmitted as untyped attribute maps: [`ResponseProtocolState`] in
+//! snapshots and [`ProtocolStateDelta`] in deltas. The [`TychoState`] trait maps these attributes
+//! onto a user defined struct, decoding each value into the type of the corresponding field.
+//!
+//! Implementations are usually derived (requires the `derive` feature):
+//!
+//! ```ignore
+//! use tycho_client::state::TychoState;
+//!
+//! #[derive(TychoState)]
+//! struct UniswapV2State {
+//!     #[tycho(component_id)]
+//!     id: String,
+//!     reserve0: u128,
+//!     reserve1: u128,
+//!     #[tycho(rename = "fee")]
+//!     fee_bps: Option<u32>,
+//!     #[tycho(skip)]
+//!     last_update: u64,
+//! }
+//! ```
+//!
+//! Fields are mapped to the attribute of the same name unless renamed. Fields of type `Option<T>`
+//! are optional: they are `None` if the attribute is missing and reset to `None` if a delta
+//! deletes the attribute. All other fields are required, a state missing them fails to decode.
+//! Skipped fields are initialised with their `Default` value and never touched by deltas.
+//!
+//! Values are decoded with [`FromAttribute`], which is implemented for integers, `bool`, `Bytes`,
+//! `Vec<u8>` and `String`. Implement it for your own types to use them as fields.
+use std::collections::HashMap;
+
+use thiserror::Error;
+#[cfg(feature = "derive")]
+pub use tycho_client_derive::TychoState;
+// Re-exported so derived implementations don't require a direct dependency on tycho-common.
+pub use tycho_common::dto::{ProtocolStateDelta, ResponseProtocolState};
+use tycho_common::Bytes;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum StateDecodeError {
+    /// A required attribute is missing or was deleted: (component_id, attribute).
+    #[error("Missing attribute {1} of component {0}")]
+    MissingAttribute(String, String),
+
+    /// An attribute value could not be decoded: (component_id, attribute, reason).
+    #[error("Failed to decode attribute {1} of component {0}: {2}")]
+    InvalidAttribute(String, String, String),
+}
+
+/// A protocol state that can be decoded from Tycho's attribute maps.
+pub trait TychoState: Sized {
+    /// Decodes the state from a snapshot.
+    fn from_state(state: &ResponseProtocolState) -> Result<Self, StateDecodeError>;
+
+    /// Applies a delta to the state.
+    ///
+    /// Attributes not mapped to any field are ignored. If the delta fails to decode, the state is
+    /// left unchanged.
+    fn apply_delta(&mut self, delta: &ProtocolStateDelta) -> Result<(), StateDecodeError>;
+}
+
+/// A type that can be decoded from an attribute value.
+pub trait FromAttribute: Sized {
+    fn from_attribute(value: &Bytes) -> Result<Self, String>;
+}
+
+/// Decodes the attribute `name` of a component.
+pub fn decode_attribute<T: FromAttribute>(
+    component_id: &str,
+    name: &str,
+    value: &Bytes,
+) -> Result<T, StateDecodeError> {
+    T::from_attribute(value).map_err(|reason| {
+        StateDecodeError::InvalidAttribute(component_id.to_string(), name.to_string(), reason)
+    })
+}
+
+/// Decodes the attribute `name` of a component, failing if it is missing.
+pub fn required_attribute<T: FromAttribute>(
+    component_id: &str,
+    attributes: &HashMap<String, Bytes>,
+    name: &str,
+) -> Result<T, StateDecodeError> {
+    let value = attributes.get(name).ok_or_else(|| {
+        StateDecodeError::MissingAttribute(component_id.to_string(), name.to_string())
+    })?;
+    decode_attribute(component_id, name, value)
+}
+
+/// Decodes the attribute `name` of a component, returning `None` if it is missing.
+pub fn optional_attribute<T: FromAttribute>(
+    component_id: &str,
+    attributes: &HashMap<String, Bytes>,
+    name: &str,
+) -> Result<Option<T>, StateDecodeError> {
+    attributes
+        .get(name)
+        .map(|value| decode_attribute(component_id, name, value))
+        .transpose()
+}
+
+/// Returns the last `size` bytes of a big endian encoded integer, failing if the dropped bytes
+/// are significant, i.e. not just padding with `fill`.
+fn fit_be_bytes(value: &[u8], size: usize, fill: u8, signed: bool) -> Result<&[u8], String> {
+    if value.len() <= size {
+        return Ok(value);
+    }
+    let (padding, rest) = value.split_at(value.len() - size);
+    let sign_preserved = !signed || (rest[0] & 0x80 != 0) == (fill == 0xff);
+    if padding.iter().all(|&b| b == fill) && sign_preserved {
+        Ok(rest)
+    } else {
+        Err(format!("0x{} does not fit into {size} bytes", hex::encode(value)))
+    }
+}
+
+macro_rules! impl_from_attribute_for_uint {
+    ($($t:ty),*) => {
+        $(
+            impl FromAttribute for $t {
+                fn from_attribute(value: &Bytes) -> Result<Self, String> {
+                    let trimmed = fit_be_bytes(value.as_ref(), std::mem::size_of::<$t>(), 0, false)?;
+                    let mut buf = [0u8; std::mem::size_of::<$t>()];
+                    buf[std::mem::size_of::<$t>() - trimmed.len()..].copy_from_slice(trimmed);
+                    Ok(<$t>::from_be_bytes(buf))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_attribute_for_uint!(u8, u16, u32, u64, u128);
+
+macro_rules! impl_from_attribute_for_signed_int {
+    ($($t:ty),*) => {
+        $(
+            impl FromAttribute for $t {
+                fn from_attribute(value: &Bytes) -> Result<Self, String> {
+                    let negative = value
+                        .first()
+                        .is_some_and(|&b| b & 0x80 != 0);
+                    let fill = if negative { 0xff } else { 0x00 };
+                    let trimmed = fit_be_bytes(value.as_ref(), std::mem::size_of::<$t>(), fill, true)?;
+                    // Sign-extend the value to the size of the integer.
+                    let mut buf = [fill; std::mem::size_of::<$t>()];
+                    buf[std::mem::size_of::<$t>() - trimmed.len()..].copy_from_slice(trimmed);
+                    Ok(<$t>::from_be_bytes(buf))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_attribute_for_signed_int!(i8, i16, i32, i64, i128);
+
+impl FromAttribute for bool {
+    fn from_attribute(value: &Bytes) -> Result<Self, String> {
+        Ok(value.iter().any(|&b| b != 0))
+    }
+}
+
+impl FromAttribute for Bytes {
+    fn from_attribute(value: &Bytes) -> Result<Self, String> {
+        Ok(value.clone())
+    }
+}
+
+impl FromAttribute for Vec<u8> {
+    fn from_attribute(value: &Bytes) -> Result<Self, String> {
+        Ok(value.to_vec())
+    }
+}
+
+impl FromAttribute for String {
+    fn from_attribute(value: &Bytes) -> Result<Self, String> {
+        String::from_utf8(value.to_vec()).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use rstest::rstest;
+    use tycho_client_derive::TychoState;
+
+    use super::*;
+
+    #[derive(TychoState, Debug, PartialEq)]
+    struct PoolState {
+        #[tycho(component_id)]
+        id: String,
+        reserve0: u128,
+        tick: i32,
+        #[tycho(rename = "fee")]
+        fee_bps: Option<u32>,
+        paused: bool,
+        #[tycho(skip)]
+        updates: u64,
+    }
+
+    fn snapshot() -> ResponseProtocolState {
+        ResponseProtocolState {
+            component_id: "pool".to_string(),
+            attributes: HashMap::from([
+                ("reserve0".to_string(), Bytes::from(1000u64).lpad(32, 0)),
+                ("tick".to_string(), Bytes::from((-5i32).to_be_bytes().to_vec())),
+                ("paused".to_string(), Bytes::from(vec![0u8])),
+                ("unmapped".to_string(), Bytes::from(vec![1u8])),
+            ]),
+            balances: HashMap::new(),
+        }
+    }
+
+    fn delta(
+        updated: impl IntoIterator<Item = (&'static str, Bytes)>,
+        deleted: impl IntoIterator<Item = &'static str>,
+    ) -> ProtocolStateDelta {
+        ProtocolStateDelta {
+            component_id: "pool".to_string(),
+            updated_attributes: updated
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+            deleted_attributes: deleted
+                .into_iter()
+                .map(str::to_string)
+                .collect::<HashSet<_>>(),
+        }
+    }
+
+    #[test]
+    fn test_from_state() {
+        let state = PoolState::from_state(&snapshot()).unwrap();
+
+        assert_eq!(
+            state,
+            PoolState {
+                id: "pool".to_string(),
+                reserve0: 1000,
+                tick: -5,
+                fee_bps: None,
+                paused: false,
+                updates: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_state_missing_attribute() {
+        let mut snapshot = snapshot();
+        snapshot.attributes.remove("tick");
+
+        let res = PoolState::from_state(&snapshot);
+
+        assert_eq!(
+            res,
+            Err(StateDecodeError::MissingAttribute("pool".to_string(), "tick".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_apply_delta() {
+        let mut state = PoolState::from_state(&snapshot()).unwrap();
+
+        state
+            .apply_delta(&delta(
+                [("fee", Bytes::from(30u32)), ("reserve0", Bytes::from(2000u64))],
+                [],
+            ))
+            .unwrap();
+        assert_eq!(state.reserve0, 2000);
+        assert_eq!(state.fee_bps, Some(30));
+
+        state
+            .apply_delta(&delta([], ["fee", "unmapped"]))
+            .unwrap();
+        assert_eq!(state.fee_bps, None);
+    }
+
+    #[rstest]
+    #[case::deleted_required(delta([("reserve0", Bytes::from(1u8))], ["tick"]))]
+    #[case::invalid_value(delta([("reserve0", Bytes::from(1u8)), ("tick", Bytes::from(vec![0x01; 33]))], []))]
+    fn test_apply_delta_failure_leaves_state_unchanged(#[case] delta: ProtocolStateDelta) {
+        let mut state = PoolState::from_state(&snapshot()).unwrap();
+
+        let res = state.apply_delta(&delta);
+
+        assert!(res.is_err());
+        assert_eq!(state, PoolState::from_state(&snapshot()).unwrap());
+    }
+
+    #[rstest]
+    #[case::padded(Bytes::from(1u64).lpad(32, 0), Ok(1))]
+    #[case::short(Bytes::from(vec![0x01, 0x00]), Ok(256))]
+    #[case::empty(Bytes::default(), Ok(0))]
+    #[case::overflow(Bytes::from(u64::MAX).lpad(9, 1), Err(()))]
+    fn test_decode_uint(#[case] value: Bytes, #[case] exp: Result<u64, ()>) {
+        assert_eq!(u64::from_attribute(&value).map_err(|_| ()), exp);
+    }
+
+    #[rstest]
+    #[case::negative_padded(Bytes::from(vec![0xff; 32]), Ok(-1))]
+    #[case::negative_short(Bytes::from(vec![0xff, 0x00]), Ok(-256))]
+    #[case::positive_padded(Bytes::from(1u64).lpad(32, 0), Ok(1))]
+    #[case::sign_lost(Bytes::from(vec![0x00, 0x80, 0x00, 0x00, 0x00]), Err(()))]
+    #[case::overflow(Bytes::from(vec![0x01, 0x00, 0x00, 0x00, 0x00]), Err(()))]
+    fn test_decode_signed_int(#[case] value: Bytes, #[case] exp: Result<i32, ()>) {
+        assert_eq!(i32::from_attribute(&value).map_err(|_| ()), exp);
+    }
+}
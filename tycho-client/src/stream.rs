@@ -7,13 +7,16 @@ use std::{
 use thiserror::Error;
 use tokio::{sync::mpsc::Receiver, task::JoinHandle};
 use tracing::{info, warn};
-use tycho_common::dto::{Chain, ExtractorIdentity, PaginationParams, ProtocolSystemsRequestBody};
+use tycho_common::{
+    dto::{Chain, ExtractorIdentity, PaginationParams, ProtocolSystemsRequestBody},
+    models,
+};
 
 use crate::{
     deltas::DeltasClient,
     feed::{
-        component_tracker::ComponentFilter, synchronizer::ProtocolStateSynchronizer, BlockHeader,
-        BlockSynchronizer, FeedMessage,
+        component_tracker::ComponentFilter, progress::SyncProgressSender,
+        synchronizer::ProtocolStateSynchronizer, BlockHeader, BlockSynchronizer, FeedMessage,
     },
     rpc::RPCClient,
     HttpRPCClient, WsDeltasClient,
@@ -42,6 +45,7 @@ pub struct TychoStreamBuilder {
     auth_key: Option<String>,
     no_tls: bool,
     include_tvl: bool,
+    progress_tx: Option<SyncProgressSender>,
 }
 
 impl TychoStreamBuilder {
@@ -60,20 +64,15 @@ impl TychoStreamBuilder {
             auth_key: None,
             no_tls: true,
             include_tvl: false,
+            progress_tx: None,
         }
     }
 
     /// Returns the default block_time, timeout and max_missed_blocks values for the given
     /// blockchain network.
     fn default_timing(chain: &Chain) -> (u64, u64, u64) {
-        match chain {
-            Chain::Ethereum => (12, 36, 10),
-            Chain::Starknet => (2, 8, 50),
-            Chain::ZkSync => (3, 12, 50),
-            Chain::Arbitrum => (1, 2, 100), // Typically closer to 0.25s
-            Chain::Base => (2, 12, 50),
-            Chain::Unichain => (1, 10, 100),
-        }
+        let spec = models::Chain::from(*chain).spec();
+        (spec.block_time.as_secs(), spec.sync_timeout.as_secs(), spec.max_missed_blocks)
     }
 
     /// Adds an exchange and its corresponding filter to the Tycho client.
@@ -130,6 +129,13 @@ impl TychoStreamBuilder {
         self
     }
 
+    /// Reports startup progress (discovered components, retrieved snapshots) of all registered
+    /// exchanges to the given sender.
+    pub fn progress_sender(mut self, progress_tx: SyncProgressSender) -> Self {
+        self.progress_tx = Some(progress_tx);
+        self
+    }
+
     /// Builds and starts the Tycho client, connecting to the Tycho server and
     /// setting up the synchronization of exchange components.
     pub async fn build(
@@ -184,7 +190,7 @@ impl TychoStreamBuilder {
         for (name, filter) in self.exchanges {
             info!("Registering exchange: {}", name);
             let id = ExtractorIdentity { chain: self.chain, name: name.clone() };
-            let sync = ProtocolStateSynchronizer::new(
+            let mut sync = ProtocolStateSynchronizer::new(
                 id.clone(),
                 true,
                 filter,
@@ -195,6 +201,9 @@ impl TychoStreamBuilder {
                 ws_client.clone(),
                 self.block_time + self.timeout,
             );
+            if let Some(progress_tx) = &self.progress_tx {
+                sync = sync.with_progress_sender(progress_tx.clone());
+            }
             block_sync = block_sync.register_synchronizer(id, sync);
         }
 
@@ -0,0 +1,197 @@
+//! Registry of supported chains and their constants.
+//!
+//! Every chain supported by Tycho has a single [`Spec`] entry here. Components that need to
+//! reason about chain specific properties, such as block times, finality or address formats,
+//! should look them up through [`Chain::spec`] instead of hardcoding Ethereum values.
+use std::time::Duration;
+
+use crate::{models::Chain, Bytes};
+
+/// Format of account and token addresses on a chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressFormat {
+    /// 20 byte addresses as used by EVM chains.
+    Evm,
+    /// Field element addresses of up to 32 bytes as used by Starknet.
+    Felt,
+}
+
+impl AddressFormat {
+    /// Checks whether the given address is well-formed for this format.
+    pub fn is_valid(&self, address: &Bytes) -> bool {
+        match self {
+            AddressFormat::Evm => address.len() == 20,
+            AddressFormat::Felt => !address.is_empty() && address.len() <= 32,
+        }
+    }
+}
+
+/// Constants describing a single chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spec {
+    pub chain: Chain,
+    /// The chain id, 0 if the chain has no EVM style chain id.
+    pub chain_id: u64,
+    /// Average time between two blocks.
+    pub block_time: Duration,
+    /// Number of blocks after which a block is no longer expected to be reorged. Informational
+    /// only, extractors finalize blocks as reported by their data source.
+    pub finality_depth: u64,
+    pub native_token_symbol: &'static str,
+    pub native_token_decimals: u32,
+    /// Address of the wrapped native token, the zero address if there is none.
+    pub wrapped_native_token: &'static str,
+    pub address_format: AddressFormat,
+    /// Time a client waits for a new block before considering a synchronizer delayed.
+    pub sync_timeout: Duration,
+    /// Number of blocks a client synchronizer may fall behind before it is considered stale.
+    pub max_missed_blocks: u64,
+    /// Whether the chain is indexed with traced entry points (DCI).
+    pub supports_entrypoint_tracing: bool,
+}
+
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+const ETHEREUM: Spec = Spec {
+    chain: Chain::Ethereum,
+    chain_id: 1,
+    block_time: Duration::from_secs(12),
+    finality_depth: 64,
+    native_token_symbol: "ETH",
+    native_token_decimals: 18,
+    wrapped_native_token: "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+    address_format: AddressFormat::Evm,
+    sync_timeout: Duration::from_secs(36),
+    max_missed_blocks: 10,
+    supports_entrypoint_tracing: true,
+};
+
+// It was decided that STRK token will be tracked as a dedicated AccountBalance on Starknet
+// accounts and ETH balances will be tracked as a native balance.
+const STARKNET: Spec = Spec {
+    chain: Chain::Starknet,
+    chain_id: 0,
+    block_time: Duration::from_secs(2),
+    finality_depth: 16,
+    native_token_symbol: "ETH",
+    native_token_decimals: 18,
+    // Starknet does not have a wrapped native token
+    wrapped_native_token: ZERO_ADDRESS,
+    address_format: AddressFormat::Felt,
+    sync_timeout: Duration::from_secs(8),
+    max_missed_blocks: 50,
+    supports_entrypoint_tracing: false,
+};
+
+const ZKSYNC: Spec = Spec {
+    chain: Chain::ZkSync,
+    chain_id: 324,
+    block_time: Duration::from_secs(3),
+    finality_depth: 64,
+    native_token_symbol: "ETH",
+    native_token_decimals: 18,
+    wrapped_native_token: "0x5AEa5775959fBC2557Cc8789bC1bf90A239D9a91",
+    address_format: AddressFormat::Evm,
+    sync_timeout: Duration::from_secs(12),
+    max_missed_blocks: 50,
+    supports_entrypoint_tracing: false,
+};
+
+const ARBITRUM: Spec = Spec {
+    chain: Chain::Arbitrum,
+    chain_id: 42161,
+    // Typically closer to 0.25s
+    block_time: Duration::from_secs(1),
+    finality_depth: 240,
+    native_token_symbol: "ETH",
+    native_token_decimals: 18,
+    wrapped_native_token: "0x82aF49447D8a07e3bd95BD0d56f35241523fBab1",
+    address_format: AddressFormat::Evm,
+    sync_timeout: Duration::from_secs(2),
+    max_missed_blocks: 100,
+    supports_entrypoint_tracing: false,
+};
+
+const BASE: Spec = Spec {
+    chain: Chain::Base,
+    chain_id: 8453,
+    block_time: Duration::from_secs(2),
+    finality_depth: 30,
+    native_token_symbol: "ETH",
+    native_token_decimals: 18,
+    wrapped_native_token: "0x4200000000000000000000000000000000000006",
+    address_format: AddressFormat::Evm,
+    sync_timeout: Duration::from_secs(12),
+    max_missed_blocks: 50,
+    supports_entrypoint_tracing: false,
+};
+
+const UNICHAIN: Spec = Spec {
+    chain: Chain::Unichain,
+    chain_id: 130,
+    block_time: Duration::from_secs(1),
+    finality_depth: 60,
+    native_token_symbol: "ETH",
+    native_token_decimals: 18,
+    wrapped_native_token: "0x4200000000000000000000000000000000000006",
+    address_format: AddressFormat::Evm,
+    sync_timeout: Duration::from_secs(10),
+    max_missed_blocks: 100,
+    supports_entrypoint_tracing: false,
+};
+
+/// Specs of all supported chains.
+pub const SPECS: [&Spec; 6] = [&ETHEREUM, &STARKNET, &ZKSYNC, &ARBITRUM, &BASE, &UNICHAIN];
+
+impl Chain {
+    /// Returns the constants of this chain.
+    pub const fn spec(&self) -> &'static Spec {
+        match self {
+            Chain::Ethereum => &ETHEREUM,
+            Chain::Starknet => &STARKNET,
+            Chain::ZkSync => &ZKSYNC,
+            Chain::Arbitrum => &ARBITRUM,
+            Chain::Base => &BASE,
+            Chain::Unichain => &UNICHAIN,
+        }
+    }
+}
+
+impl Spec {
+    /// Checks whether the given address is well-formed on this chain.
+    pub fn is_valid_address(&self, address: &Bytes) -> bool {
+        self.address_format.is_valid(address)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use rstest::rstest;
+
+    use super::*;
+
+    #[test]
+    fn test_specs_match_chain() {
+        for spec in SPECS {
+            assert_eq!(spec.chain.spec(), spec);
+        }
+    }
+
+    #[rstest]
+    #[case::evm(Chain::Ethereum, "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", true)]
+    #[case::evm_too_short(Chain::Ethereum, "0x01", false)]
+    #[case::felt(
+        Chain::Starknet,
+        "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7",
+        true
+    )]
+    #[case::felt_short(Chain::Starknet, "0x01", true)]
+    #[case::felt_empty(Chain::Starknet, "0x", false)]
+    fn test_is_valid_address(#[case] chain: Chain, #[case] address: &str, #[case] exp: bool) {
+        let address = Bytes::from_str(address).unwrap();
+
+        assert_eq!(chain.spec().is_valid_address(&address), exp);
+    }
+}
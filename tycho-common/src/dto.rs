@@ -11,7 +11,7 @@ use std::{
     hash::{Hash, Hasher},
 };
 
-use chrono::{NaiveDateTime, Utc};
+use chrono::{NaiveDate, NaiveDateTime, Utc};
 use serde::{de, Deserialize, Deserializer, Serialize};
 use strum_macros::{Display, EnumString};
 use utoipa::{IntoParams, ToSchema};
@@ -232,6 +232,9 @@ pub struct BlockChanges {
     pub revert: bool,
     #[serde(with = "hex_hashmap_key", default)]
     pub new_tokens: HashMap<Bytes, ResponseToken>,
+    /// Updates of accounts, by address. Updates of shared accounts observed by another
+    /// extractor are included in the first message of a later block, i.e. they may belong to a
+    /// previous block.
     #[serde(alias = "account_deltas", with = "hex_hashmap_key")]
     pub account_updates: HashMap<Bytes, AccountUpdate>,
     #[serde(alias = "state_deltas")]
@@ -574,10 +577,23 @@ pub struct ProtocolComponent {
     pub creation_tx: Bytes,
     /// Date time of creation in UTC time
     pub created_at: NaiveDateTime,
+    /// URI of further metadata about the component, taken from the `manifest_uri` static
+    /// attribute if it is valid.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest_uri: Option<String>,
+    /// Factory contract that created the component, taken from the `factory` static attribute
+    /// if it is valid.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type=Option<String>)]
+    pub factory: Option<Bytes>,
 }
 
 impl From<models::protocol::ProtocolComponent> for ProtocolComponent {
     fn from(value: models::protocol::ProtocolComponent) -> Self {
+        // Invalid metadata is only omitted from the typed fields, the raw static attributes are
+        // kept unchanged. It is reported once when the component is extracted.
+        let manifest_uri = value.manifest_uri().ok().flatten();
+        let factory = value.factory().ok().flatten();
         Self {
             id: value.id,
             protocol_system: value.protocol_system,
@@ -589,6 +605,8 @@ impl From<models::protocol::ProtocolComponent> for ProtocolComponent {
             change: value.change.into(),
             creation_tx: value.creation_tx,
             created_at: value.created_at,
+            manifest_uri,
+            factory,
         }
     }
 }
@@ -1537,6 +1555,90 @@ impl ComponentTvlRequestResponse {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, ToSchema, Eq, Hash, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ComponentFeesRequestBody {
+    #[serde(default)]
+    pub chain: Chain,
+    /// Filters protocol components by protocol system
+    /// Useful when `component_ids` is omitted to fetch all components under a specific system.
+    #[serde(alias = "protocolSystem")]
+    pub protocol_system: Option<String>,
+    #[serde(default)]
+    pub component_ids: Option<Vec<String>>,
+    /// First day (UTC) to include.
+    pub start_date: NaiveDate,
+    /// Last day (UTC) to include.
+    pub end_date: NaiveDate,
+    #[serde(default)]
+    pub pagination: PaginationParams,
+}
+
+impl ComponentFeesRequestBody {
+    pub fn id_filtered(
+        ids: Vec<String>,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        chain: Chain,
+    ) -> Self {
+        Self {
+            chain,
+            protocol_system: None,
+            component_ids: Some(ids),
+            start_date,
+            end_date,
+            pagination: Default::default(),
+        }
+    }
+}
+
+/// Fees accrued by a component within a single day (UTC).
+///
+/// Amounts are denominated in `token` and given in its smallest unit, as big-endian unsigned
+/// integers. The float amounts are lossy.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct ComponentDailyFees {
+    pub component_id: String,
+    #[serde(with = "hex_bytes")]
+    #[schema(value_type=String)]
+    pub token: Bytes,
+    pub day: NaiveDate,
+    /// Fees paid to liquidity providers.
+    #[schema(value_type=String)]
+    pub lp_fees: Bytes,
+    pub lp_fees_float: f64,
+    /// Fees collected by the protocol.
+    #[schema(value_type=String)]
+    pub protocol_fees: Bytes,
+    pub protocol_fees_float: f64,
+}
+
+impl From<models::protocol::ComponentDailyFees> for ComponentDailyFees {
+    fn from(value: models::protocol::ComponentDailyFees) -> Self {
+        Self {
+            component_id: value.component_id,
+            token: value.token,
+            day: value.day,
+            lp_fees: value.lp_fees,
+            lp_fees_float: value.lp_fees_float,
+            protocol_fees: value.protocol_fees,
+            protocol_fees_float: value.protocol_fees_float,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct ComponentFeesRequestResponse {
+    pub fees: Vec<ComponentDailyFees>,
+    pub pagination: PaginationResponse,
+}
+
+impl ComponentFeesRequestResponse {
+    pub fn new(fees: Vec<ComponentDailyFees>, pagination: PaginationResponse) -> Self {
+        Self { fees, pagination }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, PartialEq, ToSchema, Eq, Hash, Clone)]
 pub struct TracedEntryPointRequestBody {
     #[serde(default)]
@@ -1,3 +1,4 @@
+pub mod chain;
 pub mod dto;
 pub mod hex_bytes;
 pub mod models;
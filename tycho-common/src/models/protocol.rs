@@ -1,18 +1,38 @@
 use std::collections::{hash_map::Entry, HashMap, HashSet};
 
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use tracing::warn;
 
 use crate::{
     models::{
-        blockchain::Transaction, token::Token, Address, AttrStoreKey, Balance, Chain, ChangeType,
-        ComponentId, MergeError, StoreVal, TxHash,
+        blockchain::Transaction, token::Token, Address, AttrStoreKey, Balance, BlockHash, Chain,
+        ChangeType, ComponentId, MergeError, StoreVal, TxHash,
     },
     Bytes,
 };
 
+/// Static attribute holding a URI to further metadata about a component, e.g. a pool manifest.
+/// The value is the UTF-8 encoded URI.
+pub const MANIFEST_URI_ATTRIBUTE: &str = "manifest_uri";
+/// Static attribute holding the address of the factory contract that created a component.
+pub const FACTORY_ATTRIBUTE: &str = "factory";
+
+/// Manifest uris are meant to be rendered by UIs, so only schemes that can't execute code are
+/// accepted.
+const MANIFEST_URI_SCHEMES: [&str; 4] = ["https", "http", "ipfs", "ar"];
+const MAX_MANIFEST_URI_LENGTH: usize = 2048;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ComponentMetadataError {
+    #[error("Invalid manifest uri of component {0}: {1}")]
+    InvalidManifestUri(ComponentId, String),
+    #[error("Invalid factory address of component {0}: {1}")]
+    InvalidFactory(ComponentId, Bytes),
+}
+
 /// `ProtocolComponent` provides detailed descriptions of a component of a protocol,
 /// for example, swap pools that enables the exchange of two tokens.
 ///
@@ -65,6 +85,67 @@ impl ProtocolComponent {
             created_at,
         }
     }
+
+    /// Returns the uri of the `manifest_uri` static attribute, if present.
+    ///
+    /// The uri must be valid UTF-8 without whitespace, use one of the `https`, `http`, `ipfs` or
+    /// `ar` schemes and be at most 2048 bytes long.
+    pub fn manifest_uri(&self) -> Result<Option<String>, ComponentMetadataError> {
+        let Some(value) = self
+            .static_attributes
+            .get(MANIFEST_URI_ATTRIBUTE)
+        else {
+            return Ok(None);
+        };
+        let invalid = |reason: &str| {
+            ComponentMetadataError::InvalidManifestUri(self.id.clone(), reason.into())
+        };
+
+        if value.len() > MAX_MANIFEST_URI_LENGTH {
+            return Err(invalid("uri is too long"));
+        }
+        let uri = std::str::from_utf8(value).map_err(|_| invalid("uri is not valid UTF-8"))?;
+        if uri
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control())
+        {
+            return Err(invalid("uri contains whitespace or control characters"));
+        }
+        match uri.split_once("://") {
+            Some((scheme, rest))
+                if !rest.is_empty() &&
+                    MANIFEST_URI_SCHEMES
+                        .iter()
+                        .any(|s| s.eq_ignore_ascii_case(scheme)) =>
+            {
+                Ok(Some(uri.to_string()))
+            }
+            _ => Err(invalid("unsupported uri scheme")),
+        }
+    }
+
+    /// Returns the address of the `factory` static attribute, if present.
+    ///
+    /// The address must be well-formed for the chain of the component.
+    pub fn factory(&self) -> Result<Option<Address>, ComponentMetadataError> {
+        match self
+            .static_attributes
+            .get(FACTORY_ATTRIBUTE)
+        {
+            Some(factory)
+                if self
+                    .chain
+                    .spec()
+                    .is_valid_address(factory) =>
+            {
+                Ok(Some(factory.clone()))
+            }
+            Some(factory) => {
+                Err(ComponentMetadataError::InvalidFactory(self.id.clone(), factory.clone()))
+            }
+            None => Ok(None),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -200,6 +281,47 @@ impl ComponentBalance {
     }
 }
 
+/// Fees accrued by a component within a single block.
+///
+/// Amounts are denominated in `token` and given in its smallest unit, as big-endian unsigned
+/// integers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ComponentFeeAccrual {
+    pub component_id: ComponentId,
+    pub token: Address,
+    pub block_hash: BlockHash,
+    /// Fees paid to liquidity providers.
+    pub lp_fees: Balance,
+    /// Fees collected by the protocol.
+    pub protocol_fees: Balance,
+}
+
+impl ComponentFeeAccrual {
+    pub fn new(
+        component_id: &str,
+        token: Address,
+        block_hash: BlockHash,
+        lp_fees: Balance,
+        protocol_fees: Balance,
+    ) -> Self {
+        Self { component_id: component_id.to_string(), token, block_hash, lp_fees, protocol_fees }
+    }
+}
+
+/// Fees accrued by a component within a single day (UTC), summed over all blocks of that day.
+///
+/// The float amounts are lossy and only meant for display and sorting.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ComponentDailyFees {
+    pub component_id: ComponentId,
+    pub token: Address,
+    pub day: NaiveDate,
+    pub lp_fees: Balance,
+    pub lp_fees_float: f64,
+    pub protocol_fees: Balance,
+    pub protocol_fees_float: f64,
+}
+
 /// Token quality range filter
 ///
 /// The quality range is considered inclusive and used as a filter, will be applied as such.
@@ -509,4 +631,79 @@ mod test {
             ))
         );
     }
+
+    fn component_with_metadata(chain: Chain, attributes: &[(&str, Bytes)]) -> ProtocolComponent {
+        ProtocolComponent {
+            id: "pool".to_string(),
+            chain,
+            static_attributes: attributes
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case::missing(None, Ok(None))]
+    #[case::https(Some("https://example.com/pool.json"), Ok(Some("https://example.com/pool.json")))]
+    #[case::ipfs(
+        Some("ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi"),
+        Ok(Some("ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi"))
+    )]
+    #[case::javascript(Some("javascript://alert(1)"), Err(()))]
+    #[case::no_scheme(Some("example.com/pool.json"), Err(()))]
+    #[case::empty_path(Some("https://"), Err(()))]
+    #[case::whitespace(Some("https://example.com/ pool.json"), Err(()))]
+    fn test_manifest_uri(#[case] uri: Option<&str>, #[case] exp: Result<Option<&str>, ()>) {
+        let attributes: Vec<_> = uri
+            .map(|uri| (MANIFEST_URI_ATTRIBUTE, Bytes::from(uri.as_bytes().to_vec())))
+            .into_iter()
+            .collect();
+        let component = component_with_metadata(Chain::Ethereum, &attributes);
+
+        let res = component.manifest_uri();
+
+        assert_eq!(
+            res.as_ref()
+                .map(Option::as_deref)
+                .map_err(|_| ()),
+            exp
+        );
+    }
+
+    #[rstest]
+    #[case::evm(Chain::Ethereum, Bytes::from(vec![0x01; 20]), true)]
+    #[case::evm_too_short(Chain::Ethereum, Bytes::from(vec![0x01; 19]), false)]
+    #[case::felt(Chain::Starknet, Bytes::from(vec![0x01; 32]), true)]
+    #[case::felt_too_long(Chain::Starknet, Bytes::from(vec![0x01; 33]), false)]
+    fn test_factory(#[case] chain: Chain, #[case] factory: Bytes, #[case] valid: bool) {
+        let component = component_with_metadata(chain, &[(FACTORY_ATTRIBUTE, factory.clone())]);
+
+        let res = component.factory();
+
+        if valid {
+            assert_eq!(res, Ok(Some(factory)));
+        } else {
+            assert_eq!(
+                res,
+                Err(ComponentMetadataError::InvalidFactory("pool".to_string(), factory))
+            );
+        }
+    }
+
+    #[test]
+    fn test_dto_omits_invalid_metadata() {
+        let attributes = [
+            (MANIFEST_URI_ATTRIBUTE, Bytes::from(b"https://example.com/pool.json".to_vec())),
+            (FACTORY_ATTRIBUTE, Bytes::from(vec![0x01; 4])),
+        ];
+        let component = component_with_metadata(Chain::Ethereum, &attributes);
+
+        let res = crate::dto::ProtocolComponent::from(component.clone());
+
+        assert_eq!(res.manifest_uri, Some("https://example.com/pool.json".to_string()));
+        assert_eq!(res.factory, None);
+        assert_eq!(res.static_attributes, component.static_attributes);
+    }
 }
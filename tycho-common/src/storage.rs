@@ -5,7 +5,7 @@ use std::{
 };
 
 use async_trait::async_trait;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use thiserror::Error;
 
 use crate::{
@@ -17,8 +17,8 @@ use crate::{
         },
         contract::{Account, AccountBalance, AccountDelta},
         protocol::{
-            ComponentBalance, ProtocolComponent, ProtocolComponentState,
-            ProtocolComponentStateDelta, QualityRange,
+            ComponentBalance, ComponentDailyFees, ComponentFeeAccrual, ProtocolComponent,
+            ProtocolComponentState, ProtocolComponentStateDelta, QualityRange,
         },
         token::Token,
         Address, BlockHash, Chain, ComponentId, ContractId, EntryPointId, ExtractionState,
@@ -74,6 +74,8 @@ pub enum StorageError {
     WriteCacheGoneAway(),
     #[error("Invalid block range encountered")]
     InvalidBlockRange(),
+    #[error("Block {0} was orphaned by a reorg and is no longer part of the canonical chain!")]
+    OrphanedBlock(BlockHash),
 }
 
 /// Storage methods for chain specific objects.
@@ -184,6 +186,19 @@ pub trait ExtractionStateGateway {
     /// # Returns
     /// Ok, if state was stored successfully, Err if the state is not valid.
     async fn save_state(&self, state: &ExtractionState) -> Result<(), StorageError>;
+
+    /// Reverts the storage to a previous block and saves the state of the extractor within a
+    /// single database transaction.
+    ///
+    /// As both changes are committed together, the stored cursor never points to a reverted block.
+    ///
+    /// # Parameters
+    /// - `state` The state of the extractor after the revert. Its block is the block to revert to,
+    ///   see `ChainGateway::revert_state`.
+    ///
+    /// # Returns
+    /// Ok if the revert was committed, Err otherwise. On errors the storage is left unchanged.
+    async fn revert_extraction_state(&self, state: &ExtractionState) -> Result<(), StorageError>;
 }
 
 /// Point in time as either block or timestamp. If a block is chosen it
@@ -506,6 +521,44 @@ pub trait ProtocolGateway {
         ids: Option<&[&str]>,
         pagination_params: Option<&PaginationParams>,
     ) -> Result<WithTotal<HashMap<String, f64>>, StorageError>;
+
+    /// Stores the fees accrued by components.
+    ///
+    /// Accruals are also added to the daily fee rollups of their component. An accrual is only
+    /// stored once per component, token and block, repeated inserts are ignored.
+    ///
+    /// # Parameters
+    /// - `accruals` The fee accruals to store, the blocks they refer to must already exist.
+    ///
+    /// # Return
+    /// Ok if all accruals were stored. Err if a component, token or block can't be found.
+    async fn add_component_fee_accruals(
+        &self,
+        accruals: &[ComponentFeeAccrual],
+    ) -> Result<(), StorageError>;
+
+    /// Retrieve the daily fee rollups of components.
+    ///
+    /// # Parameters
+    /// - `chain` The chain for which to retrieve the fees
+    /// - `system` The protocol system for which to retrieve the fees
+    /// - `ids` The ids of the components to retrieve the fees for
+    /// - `start` The first day to include
+    /// - `end` The last day to include
+    /// - `pagination_params` Optional pagination parameters to control the number of results.
+    ///
+    /// # Return
+    /// A result with the daily fees ordered by component, token and day. Err if storage access
+    /// failed.
+    async fn get_component_daily_fees(
+        &self,
+        chain: &Chain,
+        system: Option<String>,
+        ids: Option<&[&str]>,
+        start: NaiveDate,
+        end: NaiveDate,
+        pagination_params: Option<&PaginationParams>,
+    ) -> Result<WithTotal<Vec<ComponentDailyFees>>, StorageError>;
 }
 
 /// Filters for entry points queries in the database.
@@ -177,7 +177,7 @@ impl EVMAccountExtractor {
             number: block.number.unwrap().as_u64(),
             hash: block.hash.unwrap().to_bytes(),
             parent_hash: block.parent_hash.to_bytes(),
-            chain: Chain::Ethereum,
+            chain: self.chain,
             ts: NaiveDateTime::from_timestamp_opt(block.timestamp.as_u64() as i64, 0)
                 .expect("Failed to convert timestamp"),
         })
@@ -33,7 +33,7 @@ pub enum Command {
     /// Starts a job to analyze stored tokens for tax and gas cost.
     AnalyzeTokens(AnalyzeTokenArgs),
     /// Starts Tycho RPC only. No extractors.
-    Rpc,
+    Rpc(RpcArgs),
 }
 
 #[derive(Parser, Debug, Clone, PartialEq, Eq)]
@@ -99,6 +99,29 @@ pub struct IndexArgs {
     /// Any data before this date is not kept in storage.
     #[clap(long, env, default_value = "2024-01-01T00:00:00")]
     pub retention_horizon: String,
+
+    /// Extractor to write a debug bundle for
+    ///
+    /// Optional. If provided together with `--debug-bundle-block`, the write operations of that
+    /// block are traced and written to a zip archive together with the decoded block message.
+    /// Overrides the `debug_bundle` of the extractor's config.
+    #[clap(long, requires = "debug_bundle_block")]
+    pub debug_bundle_extractor: Option<String>,
+
+    /// Block to write a debug bundle for, requires `--debug-bundle-extractor`
+    #[clap(long, requires = "debug_bundle_extractor")]
+    pub debug_bundle_block: Option<u64>,
+
+    /// Directory debug bundles are written to
+    #[clap(long, default_value = "./debug-bundles")]
+    pub debug_bundle_dir: String,
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct RpcArgs {
+    /// A comma separated list of blockchains to serve
+    #[clap(long, default_value = "ethereum", value_delimiter = ',')]
+    pub chains: Vec<String>,
 }
 
 #[derive(Args, Debug, Clone, PartialEq)]
@@ -148,6 +171,17 @@ pub struct RunSpkgArgs {
     /// - `rpc` - RPC is used to trace and retrieve detected accounts.
     #[clap(long)]
     pub dci_plugin: Option<String>,
+
+    /// Block to write a debug bundle for
+    ///
+    /// Optional. If provided, the write operations of this block are traced and written to a zip
+    /// archive together with the decoded block message, also if writing the block fails.
+    #[clap(long)]
+    pub debug_bundle_block: Option<u64>,
+
+    /// Directory debug bundles are written to
+    #[clap(long, default_value = "./debug-bundles")]
+    pub debug_bundle_dir: String,
 }
 
 impl RunSpkgArgs {
@@ -214,6 +248,8 @@ mod cli_tests {
             "17361664",
             "--protocol-type-names",
             "pt1,pt2",
+            "--debug-bundle-block",
+            "17361665",
         ])
         .expect("parse errored");
 
@@ -240,6 +276,8 @@ mod cli_tests {
                 initialized_accounts: vec![],
                 initialization_block: 0,
                 dci_plugin: None,
+                debug_bundle_block: Some(17361665),
+                debug_bundle_dir: "./debug-bundles".to_string(),
             }),
         };
 
@@ -261,6 +299,10 @@ mod cli_tests {
             "/opt/extractors.yaml",
             "--api_token",
             "your_api_token",
+            "--debug-bundle-extractor",
+            "vm:curve",
+            "--debug-bundle-block",
+            "17361665",
         ])
         .expect("parse errored");
 
@@ -281,12 +323,49 @@ mod cli_tests {
                 chains: vec!["ethereum".to_string()],
                 extractors_config: "/opt/extractors.yaml".to_string(),
                 retention_horizon: "2024-01-01T00:00:00".to_string(),
+                debug_bundle_extractor: Some("vm:curve".to_string()),
+                debug_bundle_block: Some(17361665),
+                debug_bundle_dir: "./debug-bundles".to_string(),
             }),
         };
 
         assert_eq!(cli, expected_args);
     }
 
+    #[test]
+    fn test_arg_parsing_rpc_cmd() {
+        let cli = Cli::try_parse_from(vec![
+            "tycho-indexer",
+            "--rpc-url",
+            "http://example.com",
+            "rpc",
+            "--chains",
+            "ethereum,base",
+        ])
+        .expect("parse errored");
+
+        assert_eq!(
+            cli.command(),
+            Command::Rpc(RpcArgs { chains: vec!["ethereum".to_string(), "base".to_string()] })
+        );
+    }
+
+    #[test]
+    fn test_arg_parsing_index_cmd_debug_bundle_requires_extractor() {
+        let args = Cli::try_parse_from(vec![
+            "tycho-indexer",
+            "--rpc-url",
+            "http://example.com",
+            "index",
+            "--api_token",
+            "your_api_token",
+            "--debug-bundle-block",
+            "17361665",
+        ]);
+
+        assert!(args.is_err());
+    }
+
     #[test]
     fn test_arg_parsing_missing_val() {
         let args = Cli::try_parse_from(vec![
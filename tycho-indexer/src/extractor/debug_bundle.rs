@@ -0,0 +1,208 @@
+//! Debug bundles for single blocks.
+//!
+//! Reproducing gateway issues usually requires a full local environment. Instead, an extractor
+//! can be configured to trace the write operations of a chosen block, either through the
+//! `debug_bundle` of its config or the `--debug-bundle-*` options of the `index` and `run`
+//! commands. Once that block is committed, a zip archive is written containing:
+//!
+//! - `message.txt`: the decoded block message as it was passed to the gateway.
+//! - `trace.json`: the [`WriteTrace`] of the database transaction, i.e. the executed write
+//!   operations with the rows they wrote per table, their timings and errors. The SQL text of the
+//!   statements and reads are not recorded.
+//! - `summary.txt`: a human readable overview of the trace.
+//!
+//! The bundle is written even if the block failed, whether in the database transaction or before
+//! it was submitted, so it can be attached to bug reports right away. In the latter case the
+//! trace holds no operations, only the error.
+use std::{
+    fmt::Write as _,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use thiserror::Error;
+use tycho_storage::postgres::trace::WriteTrace;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::extractor::models::BlockChanges;
+
+fn default_output_dir() -> PathBuf {
+    PathBuf::from("./debug-bundles")
+}
+
+/// Configures for which block an extractor writes a debug bundle.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct DebugBundleConfig {
+    /// Number of the block to trace.
+    pub block: u64,
+    /// Directory the bundle is written to, created if it does not exist.
+    #[serde(default = "default_output_dir")]
+    pub output_dir: PathBuf,
+}
+
+impl DebugBundleConfig {
+    pub fn new(block: u64, output_dir: PathBuf) -> Self {
+        Self { block, output_dir }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum DebugBundleError {
+    #[error("Failed to write debug bundle: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to create debug bundle archive: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("Failed to serialize trace: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Writes the debug bundle for `changes` into `output_dir` and returns the path of the archive.
+pub fn write_bundle(
+    output_dir: &Path,
+    extractor: &str,
+    changes: &BlockChanges,
+    trace: &WriteTrace,
+) -> Result<PathBuf, DebugBundleError> {
+    fs::create_dir_all(output_dir)?;
+    // extractor names may contain characters that are not allowed in file names, e.g. `vm:curve`
+    let file_name = format!(
+        "{}_{}.zip",
+        extractor.replace(|c: char| !c.is_alphanumeric(), "_"),
+        changes.block.number
+    );
+    let path = output_dir.join(file_name);
+
+    let mut zip = ZipWriter::new(File::create(&path)?);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file("message.txt", options)?;
+    zip.write_all(format!("{changes:#?}").as_bytes())?;
+
+    zip.start_file("trace.json", options)?;
+    zip.write_all(&serde_json::to_vec_pretty(trace)?)?;
+
+    zip.start_file("summary.txt", options)?;
+    zip.write_all(summary(extractor, trace).as_bytes())?;
+
+    zip.finish()?;
+    Ok(path)
+}
+
+fn summary(extractor: &str, trace: &WriteTrace) -> String {
+    let mut summary = String::new();
+    let _ = writeln!(summary, "extractor: {extractor}");
+    let _ = writeln!(
+        summary,
+        "blocks: {} ({:#x}) - {} ({:#x})",
+        trace.start_block.number,
+        trace.start_block.hash,
+        trace.end_block.number,
+        trace.end_block.hash
+    );
+    let _ = writeln!(summary, "attempts: {}", trace.attempts);
+    let _ = writeln!(summary, "duration: {:?}", trace.duration);
+    let _ = writeln!(summary, "rows: {}", trace.rows());
+    let _ = writeln!(summary, "error: {}", trace.error.as_deref().unwrap_or("-"));
+    let _ = writeln!(summary);
+    for op in trace.operations.iter() {
+        let _ = writeln!(
+            summary,
+            "{:<32} entities={:<8} rows={:<8} duration={:?}{}",
+            op.operation,
+            op.entities,
+            op.tables
+                .as_ref()
+                .map_or("?".to_string(), |_| op.rows().to_string()),
+            op.duration,
+            op.error
+                .as_ref()
+                .map(|e| format!(" error={e}"))
+                .unwrap_or_default()
+        );
+        for (table, rows) in op.tables.iter().flatten() {
+            let _ = writeln!(
+                summary,
+                "    {:<28} inserted={} updated={} deleted={}",
+                table, rows.inserted, rows.updated, rows.deleted
+            );
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeMap, io::Read, time::Duration};
+
+    use tycho_common::models::{blockchain::Block, Chain};
+    use tycho_storage::postgres::trace::{TableRows, WriteOpTrace};
+    use zip::ZipArchive;
+
+    use super::*;
+
+    #[test]
+    fn test_write_bundle() {
+        let block =
+            Block::new(2, Chain::Ethereum, "0x02".into(), "0x01".into(), Default::default());
+        let changes = BlockChanges { block: block.clone(), ..Default::default() };
+        let trace = WriteTrace {
+            owner: Some("vm:ambient".to_string()),
+            start_block: block.clone(),
+            end_block: block,
+            attempts: 1,
+            operations: vec![WriteOpTrace {
+                operation: "UpsertBlock".to_string(),
+                entities: 1,
+                tables: Some(BTreeMap::from([(
+                    "block".to_string(),
+                    TableRows { inserted: 1, updated: 0, deleted: 0 },
+                )])),
+                duration: Duration::from_millis(3),
+                error: None,
+            }],
+            duration: Duration::from_millis(5),
+            error: None,
+        };
+        let output_dir = std::env::temp_dir().join(format!("debug-bundle-{}", std::process::id()));
+
+        let path = write_bundle(&output_dir, "vm:ambient", &changes, &trace).unwrap();
+
+        assert_eq!(path, output_dir.join("vm_ambient_2.zip"));
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let mut trace_json = String::new();
+        archive
+            .by_name("trace.json")
+            .unwrap()
+            .read_to_string(&mut trace_json)
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&trace_json).unwrap(),
+            serde_json::to_value(&trace).unwrap()
+        );
+        let mut summary = String::new();
+        archive
+            .by_name("summary.txt")
+            .unwrap()
+            .read_to_string(&mut summary)
+            .unwrap();
+        assert!(summary.contains("UpsertBlock"));
+        assert!(summary.contains("inserted=1 updated=0 deleted=0"));
+        assert!(archive.by_name("message.txt").is_ok());
+        fs::remove_dir_all(output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_summary_unsubmitted() {
+        let block =
+            Block::new(2, Chain::Ethereum, "0x02".into(), "0x01".into(), Default::default());
+        let trace = WriteTrace::unsubmitted(&block, Some("vm:ambient"), &"missing component");
+
+        let res = summary("vm:ambient", &trace);
+
+        assert!(res.contains("attempts: 0"));
+        assert!(res.contains("rows: 0"));
+        assert!(res.contains("error: missing component"));
+    }
+}
@@ -28,6 +28,7 @@ use crate::{
 };
 
 pub mod chain_state;
+pub mod debug_bundle;
 mod dynamic_contract_indexer;
 pub mod models;
 pub mod post_processors;
@@ -36,6 +37,7 @@ pub mod protocol_cache;
 pub mod protocol_extractor;
 pub mod reorg_buffer;
 pub mod runner;
+pub mod shared_accounts;
 pub mod token_analysis_cron;
 mod u256_num;
 
@@ -1,6 +1,10 @@
 #![allow(deprecated)]
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
+use num_bigint::BigUint;
 use tycho_common::{
     models::{
         blockchain::{
@@ -8,7 +12,9 @@ use tycho_common::{
             Transaction, TxWithChanges,
         },
         contract::{AccountBalance, AccountChangesWithTx},
-        protocol::{ComponentBalance, ProtocolChangesWithTx, ProtocolComponent},
+        protocol::{
+            ComponentBalance, ComponentFeeAccrual, ProtocolChangesWithTx, ProtocolComponent,
+        },
         token::Token,
         AccountToContractStore, Address, AttrStoreKey, Chain, ComponentId,
     },
@@ -21,6 +27,25 @@ use crate::extractor::{
     StateUpdateBufferEntry,
 };
 
+/// Prefix of state attributes reporting LP fees accrued by a component, followed by the hex
+/// encoded address of the token the fees are denominated in, e.g. `fees/lp/0xc02a...`.
+pub const LP_FEES_ATTRIBUTE_PREFIX: &str = "fees/lp/";
+/// Prefix of state attributes reporting protocol fees accrued by a component, see
+/// [`LP_FEES_ATTRIBUTE_PREFIX`].
+pub const PROTOCOL_FEES_ATTRIBUTE_PREFIX: &str = "fees/protocol/";
+
+/// Adds a fee amount to a total, both big-endian unsigned integers.
+///
+/// Returns the sum as 32 byte integer, or None if it exceeds 32 bytes.
+fn add_fee_amounts(total: &Bytes, amount: &Bytes) -> Option<Bytes> {
+    let sum = BigUint::from_bytes_be(total) + BigUint::from_bytes_be(amount);
+    let sum = Bytes::from(sum.to_bytes_be());
+    if sum.len() > 32 {
+        return None;
+    }
+    Some(sum.lpad(32, 0))
+}
+
 /// A container for account updates grouped by transaction.
 ///
 /// Hold the detailed state changes for a block alongside with protocol
@@ -159,6 +184,9 @@ pub struct BlockChanges {
     /// finalized.
     /// Populated by the `DynamicContractIndexer`
     pub trace_results: Vec<TracedEntryPoint>,
+    /// Fees accrued within this block, summed over all transactions. Populated by
+    /// [`BlockChanges::extract_fee_accruals`].
+    pub fee_accruals: HashMap<ComponentId, HashMap<Address, ComponentFeeAccrual>>,
 }
 
 impl BlockChanges {
@@ -181,9 +209,90 @@ impl BlockChanges {
             txs_with_update,
             block_storage_changes,
             trace_results: Vec::new(),
+            fee_accruals: HashMap::new(),
         }
     }
 
+    /// Moves fee figures reported through state attributes into `fee_accruals`.
+    ///
+    /// Substreams packages report fees as state attributes named `fees/lp/<token>` or
+    /// `fees/protocol/<token>`, the value being the amount of `token` (in its smallest unit)
+    /// accrued by the component within the transaction. These attributes are not part of the
+    /// protocol state, so they are removed from the state updates. Deltas that only carried fee
+    /// attributes are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `ExtractionError` if the token address or amount of a fee attribute can't be
+    /// decoded.
+    pub fn extract_fee_accruals(&mut self) -> Result<(), ExtractionError> {
+        for tx in self.txs_with_update.iter_mut() {
+            let mut fee_only_deltas = Vec::new();
+            for delta in tx.state_updates.values_mut() {
+                let fee_attributes: Vec<_> = delta
+                    .updated_attributes
+                    .keys()
+                    .filter(|attr| {
+                        attr.starts_with(LP_FEES_ATTRIBUTE_PREFIX) ||
+                            attr.starts_with(PROTOCOL_FEES_ATTRIBUTE_PREFIX)
+                    })
+                    .cloned()
+                    .collect();
+                if fee_attributes.is_empty() {
+                    continue;
+                }
+
+                for attr in fee_attributes {
+                    let value = delta
+                        .updated_attributes
+                        .remove(&attr)
+                        .unwrap_or_default();
+                    let (is_lp_fee, token) = match attr.strip_prefix(LP_FEES_ATTRIBUTE_PREFIX) {
+                        Some(token) => (true, token),
+                        None => (false, &attr[PROTOCOL_FEES_ATTRIBUTE_PREFIX.len()..]),
+                    };
+                    let token = Address::from_str(token).map_err(|err| {
+                        ExtractionError::DecodeError(format!(
+                            "Invalid token in fee attribute {attr} of {}: {err}",
+                            delta.component_id
+                        ))
+                    })?;
+                    let accrual = self
+                        .fee_accruals
+                        .entry(delta.component_id.clone())
+                        .or_default()
+                        .entry(token.clone())
+                        .or_insert_with(|| {
+                            ComponentFeeAccrual::new(
+                                &delta.component_id,
+                                token,
+                                self.block.hash.clone(),
+                                Bytes::zero(32),
+                                Bytes::zero(32),
+                            )
+                        });
+                    let total =
+                        if is_lp_fee { &mut accrual.lp_fees } else { &mut accrual.protocol_fees };
+                    // Amounts are summed exactly, the rollups are stored as 32 byte integers.
+                    *total = add_fee_amounts(total, &value).ok_or_else(|| {
+                        ExtractionError::DecodeError(format!(
+                            "Invalid amount in fee attribute {attr} of {}",
+                            delta.component_id
+                        ))
+                    })?;
+                }
+
+                if delta.updated_attributes.is_empty() && delta.deleted_attributes.is_empty() {
+                    fee_only_deltas.push(delta.component_id.clone());
+                }
+            }
+            for component_id in fee_only_deltas {
+                tx.state_updates.remove(&component_id);
+            }
+        }
+        Ok(())
+    }
+
     /// Aggregates component and account updates.
     ///
     /// This function aggregates all protocol updates into a [`BlockAggregatedChanges`] object. This
@@ -380,6 +489,7 @@ impl From<BlockContractChanges> for BlockChanges {
                 .collect(),
             block_storage_changes: Vec::new(),
             trace_results: Vec::new(),
+            fee_accruals: HashMap::new(),
         }
     }
 }
@@ -400,6 +510,7 @@ impl From<BlockEntityChanges> for BlockChanges {
                 .collect(),
             block_storage_changes: Vec::new(),
             trace_results: Vec::new(),
+            fee_accruals: HashMap::new(),
         }
     }
 }
@@ -443,6 +554,7 @@ pub mod fixtures {
                 txs_with_update,
                 block_storage_changes: Vec::new(),
                 trace_results: Vec::new(),
+                fee_accruals: HashMap::new(),
             }
         }
     }
@@ -793,6 +905,7 @@ mod test {
     use std::str::FromStr;
 
     use prost::Message;
+    use tycho_common::models::protocol::ProtocolComponentStateDelta;
 
     use super::*;
 
@@ -958,4 +1071,85 @@ mod test {
             )])
         )
     }
+
+    #[test]
+    fn test_extract_fee_accruals() {
+        let weth = Bytes::from_str("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2").unwrap();
+        let lp_fees_attr = format!("{LP_FEES_ATTRIBUTE_PREFIX}{weth}");
+        let protocol_fees_attr = format!("{PROTOCOL_FEES_ATTRIBUTE_PREFIX}{weth}");
+        let tx_with_state_updates = |attributes: Vec<(&str, u64)>| TxWithChanges {
+            state_updates: HashMap::from([(
+                "pool".to_string(),
+                ProtocolComponentStateDelta::new(
+                    "pool",
+                    attributes
+                        .into_iter()
+                        .map(|(attr, value)| (attr.to_string(), Bytes::from(value).lpad(32, 0)))
+                        .collect(),
+                    HashSet::new(),
+                ),
+            )]),
+            ..Default::default()
+        };
+        let mut changes = BlockChanges {
+            block: Block { hash: Bytes::from("0x01"), ..Default::default() },
+            txs_with_update: vec![
+                tx_with_state_updates(vec![
+                    ("reserve", 1000),
+                    (lp_fees_attr.as_str(), 100),
+                    (protocol_fees_attr.as_str(), 10),
+                ]),
+                tx_with_state_updates(vec![(lp_fees_attr.as_str(), 50)]),
+            ],
+            ..Default::default()
+        };
+
+        changes.extract_fee_accruals().unwrap();
+
+        assert_eq!(
+            changes.fee_accruals,
+            HashMap::from([(
+                "pool".to_string(),
+                HashMap::from([(
+                    weth.clone(),
+                    ComponentFeeAccrual::new(
+                        "pool",
+                        weth,
+                        Bytes::from("0x01"),
+                        Bytes::from(150u64).lpad(32, 0),
+                        Bytes::from(10u64).lpad(32, 0),
+                    )
+                )])
+            )])
+        );
+        assert_eq!(
+            changes.txs_with_update[0].state_updates["pool"].updated_attributes,
+            HashMap::from([("reserve".to_string(), Bytes::from(1000u64).lpad(32, 0))])
+        );
+        assert!(changes.txs_with_update[1]
+            .state_updates
+            .is_empty());
+    }
+
+    #[test]
+    fn test_extract_fee_accruals_invalid_token() {
+        let mut changes = BlockChanges {
+            txs_with_update: vec![TxWithChanges {
+                state_updates: HashMap::from([(
+                    "pool".to_string(),
+                    ProtocolComponentStateDelta::new(
+                        "pool",
+                        HashMap::from([("fees/lp/weth".to_string(), Bytes::from(1u64))]),
+                        HashSet::new(),
+                    ),
+                )]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let res = changes.extract_fee_accruals();
+
+        assert!(matches!(res, Err(ExtractionError::DecodeError(_))));
+    }
 }
@@ -169,7 +169,7 @@ impl TryFromMessage for ProtocolComponent {
             )));
         }
 
-        Ok(Self {
+        let component = Self {
             id: msg.id.clone(),
             protocol_type_name: protocol_type.name,
             protocol_system: protocol_system.to_owned(),
@@ -180,7 +180,16 @@ impl TryFromMessage for ProtocolComponent {
             change: ChangeType::try_from_message(msg.change())?,
             creation_tx: tx_hash,
             created_at: creation_ts,
-        })
+        };
+        // Invalid metadata is kept in the static attributes but omitted from the typed fields
+        // served to clients, so it is only reported here.
+        if let Err(error) = component.manifest_uri() {
+            warn!(%error, "Invalid component manifest uri");
+        }
+        if let Err(error) = component.factory() {
+            warn!(%error, "Invalid component factory");
+        }
+        Ok(component)
     }
 }
 
@@ -686,7 +695,7 @@ impl TryFromMessage for BlockChanges {
                 .map(|change| TxWithStorageChanges::try_from_message((change, &block)))
                 .collect::<Result<Vec<TxWithStorageChanges>, ExtractionError>>()?;
 
-            Ok(Self::new(
+            let mut changes = Self::new(
                 extractor.to_string(),
                 chain,
                 block,
@@ -694,7 +703,9 @@ impl TryFromMessage for BlockChanges {
                 false,
                 txs_with_update,
                 block_storage_changes,
-            ))
+            );
+            changes.extract_fee_accruals()?;
+            Ok(changes)
         } else {
             Err(ExtractionError::Empty)
         }
@@ -819,6 +830,49 @@ mod test {
         assert_eq!(protocol_component.static_attributes, expected_attribute_map);
     }
 
+    #[test]
+    fn test_parse_protocol_component_keeps_invalid_metadata() {
+        let mut msg = fixtures::pb_protocol_component();
+        msg.static_att.extend([
+            substreams::Attribute {
+                name: "manifest_uri".to_owned(),
+                value: b"javascript://alert(1)".to_vec(),
+                change: substreams::ChangeType::Creation.into(),
+            },
+            substreams::Attribute {
+                name: "factory".to_owned(),
+                value: Bytes::from_str("0x1F98431c8aD98523631AE4a59f267346ea31F984")
+                    .unwrap()
+                    .to_vec(),
+                change: substreams::ChangeType::Creation.into(),
+            },
+        ]);
+        let protocol_types: HashMap<String, ProtocolType> =
+            HashMap::from([("WeightedPool".to_string(), ProtocolType::default())]);
+
+        let protocol_component = ProtocolComponent::try_from_message((
+            msg,
+            Chain::Ethereum,
+            "ambient",
+            &protocol_types,
+            Bytes::default(),
+            Default::default(),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            protocol_component.static_attributes["manifest_uri"],
+            Bytes::from(b"javascript://alert(1)".to_vec())
+        );
+        assert!(protocol_component
+            .manifest_uri()
+            .is_err());
+        assert_eq!(
+            protocol_component.factory(),
+            Ok(Some(Bytes::from_str("0x1F98431c8aD98523631AE4a59f267346ea31F984").unwrap()))
+        );
+    }
+
     pub fn transaction() -> Transaction {
         create_transaction(
             "0000000000000000000000000000000000000000000000000000000011121314",
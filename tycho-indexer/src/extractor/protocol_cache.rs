@@ -1,4 +1,7 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use async_trait::async_trait;
 use chrono::{Local, NaiveDateTime};
@@ -115,6 +118,23 @@ impl ProtocolMemoryCache {
         Ok(())
     }
 
+    /// Returns the addresses of `contracts` that belong to a cached component of `system`.
+    pub async fn linked_contracts(
+        &self,
+        system: &str,
+        contracts: &HashSet<Address>,
+    ) -> HashSet<Address> {
+        let guard = self.components.read().await;
+        guard
+            .get(system)
+            .into_iter()
+            .flat_map(HashMap::values)
+            .flat_map(|component| component.contract_addresses.iter())
+            .filter(|address| contracts.contains(*address))
+            .cloned()
+            .collect()
+    }
+
     #[instrument(skip_all)]
     async fn update_prices_cache(&self) -> Result<usize, StorageError> {
         let mut token_prices = self.token_prices.write().await;
@@ -33,17 +33,19 @@ use tycho_common::{
     traits::TokenPreProcessor,
     Bytes,
 };
-use tycho_storage::postgres::cache::CachedGateway;
+use tycho_storage::postgres::{cache::CachedGateway, trace::WriteTrace};
 use tycho_substreams::pb::tycho::evm::v1 as tycho_substreams;
 
 #[allow(deprecated)]
 use crate::{
     extractor::{
         chain_state::ChainState,
+        debug_bundle::{self, DebugBundleConfig},
         models::{BlockChanges, BlockContractChanges, BlockEntityChanges},
         protobuf_deserialisation::TryFromMessage,
         protocol_cache::{ProtocolDataCache, ProtocolMemoryCache},
         reorg_buffer::ReorgBuffer,
+        shared_accounts::SharedAccountStore,
         BlockUpdateWithCursor, ExtractionError, Extractor, ExtractorExtension, ExtractorMsg,
     },
     pb::sf::substreams::rpc::v2::{BlockScopedData, BlockUndoSignal, ModulesProgress},
@@ -72,6 +74,8 @@ pub struct ProtocolExtractor<G, T, E> {
     post_processor: Option<fn(BlockChanges) -> BlockChanges>,
     reorg_buffer: Mutex<ReorgBuffer<BlockUpdateWithCursor<BlockChanges>>>,
     dci_plugin: Option<Arc<Mutex<E>>>,
+    /// Fans out the changes of accounts shared with other extractors.
+    shared_accounts: Option<SharedAccountStore>,
 }
 
 impl<G, T, E> ProtocolExtractor<G, T, E>
@@ -118,6 +122,7 @@ where
                     post_processor,
                     reorg_buffer: Mutex::new(ReorgBuffer::new()),
                     dci_plugin,
+                    shared_accounts: None,
                 }
             }
             Ok((cursor, block_hash)) => {
@@ -154,6 +159,7 @@ where
                     post_processor,
                     reorg_buffer: Mutex::new(ReorgBuffer::new()),
                     dci_plugin,
+                    shared_accounts: None,
                 }
             }
             Err(err) => return Err(ExtractionError::Setup(err.to_string())),
@@ -163,6 +169,13 @@ where
         Ok(res)
     }
 
+    /// Merges updates of shared accounts published by other extractors into the emitted messages
+    /// and publishes this extractor's changes of shared accounts to them.
+    pub fn with_shared_accounts(mut self, store: SharedAccountStore) -> Self {
+        self.shared_accounts = Some(store);
+        self
+    }
+
     async fn update_cursor(&self, cursor: String) {
         let mut state = self.inner.lock().await;
         state.cursor = cursor.into();
@@ -297,7 +310,7 @@ where
             .flat_map(|b| b.clone().into_keys())
             .collect::<Vec<_>>();
 
-        let prices = self
+        let mut prices = self
             .protocol_cache
             .get_token_prices(&addresses)
             .await?
@@ -312,6 +325,10 @@ where
                 }
             })
             .collect::<HashMap<_, _>>();
+        // prices are denominated in the native token, so its own price is just its unit
+        prices
+            .entry(self.chain.native_token().address)
+            .or_insert_with(|| 10f64.powi(self.chain.spec().native_token_decimals as i32));
 
         // calculate new tvl values
         let tvl_updates = balances
@@ -775,6 +792,27 @@ where
         self.handle_tvl_changes(&mut changes)
             .await?;
 
+        // Shared accounts are only fanned out at the chain head, while syncing the extractors are
+        // at different blocks.
+        if let (Some(shared_accounts), false) = (&self.shared_accounts, is_syncing) {
+            shared_accounts
+                .publish(&self.name, &changes.block, &changes.account_deltas)
+                .await;
+            let updates = shared_accounts
+                .take_updates(&self.name, &changes.block)
+                .await;
+            // clients only need updates of contracts used by this extractor's components
+            let linked = self
+                .protocol_cache
+                .linked_contracts(&self.protocol_system, &updates.keys().cloned().collect())
+                .await;
+            changes.account_deltas.extend(
+                updates
+                    .into_iter()
+                    .filter(|(address, _)| linked.contains(address)),
+            );
+        }
+
         if !is_syncing {
             debug!(
                 new_components = changes.new_protocol_components.len(),
@@ -842,7 +880,8 @@ where
 
         let mut reorg_buffer = self.reorg_buffer.lock().await;
 
-        // Purge the buffer
+        // Purge the buffer. Only finalized blocks are stored, so the revert target is always
+        // buffered and the storage is left unchanged.
         let reverted_state = reorg_buffer
             .purge(block_hash)
             .map_err(|e| ExtractionError::ReorgBufferError(e.to_string()))?;
@@ -1184,6 +1223,12 @@ where
             .get_most_recent_block()
             .expect("Couldn't find most recent block in buffer during revert");
 
+        if let Some(shared_accounts) = &self.shared_accounts {
+            shared_accounts
+                .publish_revert(&self.name, &new_latest_block, &account_deltas)
+                .await;
+        }
+
         let revert_message = BlockAggregatedChanges {
             extractor: self.name.clone(),
             chain: self.chain,
@@ -1223,6 +1268,8 @@ pub struct ExtractorPgGateway {
     chain: Chain,
     db_tx_batch_size: usize,
     state_gateway: CachedGateway,
+    debug_bundle: Option<DebugBundleConfig>,
+    shared_accounts: Option<SharedAccountStore>,
 }
 
 #[automock]
@@ -1266,69 +1313,124 @@ impl ExtractorPgGateway {
         db_tx_batch_size: usize,
         state_gateway: CachedGateway,
     ) -> Self {
-        Self { name: name.to_owned(), chain, db_tx_batch_size, state_gateway }
+        Self {
+            name: name.to_owned(),
+            chain,
+            db_tx_batch_size,
+            state_gateway,
+            debug_bundle: None,
+            shared_accounts: None,
+        }
     }
 
-    #[instrument(skip_all)]
-    async fn save_cursor(
-        &self,
-        new_cursor: &str,
-        block_hash: BlockHash,
-    ) -> Result<(), StorageError> {
-        let state = ExtractionState::new(
-            self.name.to_string(),
-            self.chain,
-            None,
-            new_cursor.as_bytes(),
-            block_hash,
-        );
-        self.state_gateway
-            .save_state(&state)
-            .await?;
-        Ok(())
+    /// Writes a debug bundle with the traced write operations of the configured block.
+    pub fn with_debug_bundle(mut self, config: DebugBundleConfig) -> Self {
+        self.debug_bundle = Some(config);
+        self
     }
 
-    async fn get_last_extraction_state(&self) -> Result<ExtractionState, StorageError> {
-        let state = self
-            .state_gateway
-            .get_state(&self.name, &self.chain)
-            .await?;
-        Ok(state)
+    /// Skips writing changes of shared accounts another extractor already wrote.
+    pub fn with_shared_accounts(mut self, store: SharedAccountStore) -> Self {
+        self.shared_accounts = Some(store);
+        self
     }
-}
 
-#[async_trait]
-impl ExtractorGateway for ExtractorPgGateway {
-    async fn get_block(&self, block_hash: Bytes) -> Result<Block, StorageError> {
-        self.state_gateway
-            .get_block(&BlockIdentifier::Hash(block_hash))
-            .await
-    }
-    async fn get_cursor(&self) -> Result<(Vec<u8>, Bytes), StorageError> {
-        let extraction_state = self.get_last_extraction_state().await;
-        match extraction_state {
-            Ok(state) => Ok((state.cursor, state.block_hash)),
-            Err(e) => Err(e),
+    async fn write_debug_bundle(
+        &self,
+        config: &DebugBundleConfig,
+        changes: &BlockChanges,
+        trace: WriteTrace,
+    ) {
+        let output_dir = config.output_dir.clone();
+        let name = self.name.clone();
+        let changes = changes.clone();
+        let res = tokio::task::spawn_blocking(move || {
+            debug_bundle::write_bundle(&output_dir, &name, &changes, &trace)
+        })
+        .await;
+        match res {
+            Ok(Ok(path)) => info!(?path, "Wrote debug bundle"),
+            Ok(Err(err)) => error!(%err, "Failed to write debug bundle"),
+            Err(err) => error!(%err, "Debug bundle task failed"),
         }
     }
 
-    async fn ensure_protocol_types(&self, new_protocol_types: &[ProtocolType]) {
-        self.state_gateway
-            .add_protocol_types(new_protocol_types)
-            .await
-            .expect("Couldn't insert protocol types");
-    }
-
-    async fn advance(
+    /// Stages `changes` and commits them once a full batch is staged.
+    async fn write_block(
         &self,
         changes: &BlockChanges,
         new_cursor: &str,
         force_commit: bool,
     ) -> Result<(), StorageError> {
-        self.state_gateway
-            .start_transaction(&changes.block, Some(self.name.as_str()))
+        let debug_bundle = self
+            .debug_bundle
+            .as_ref()
+            .filter(|config| config.block == changes.block.number);
+        let trace_rx = if debug_bundle.is_some() {
+            Some(
+                self.state_gateway
+                    .start_traced_transaction(&changes.block, Some(self.name.as_str()))
+                    .await?,
+            )
+        } else {
+            self.state_gateway
+                .start_transaction(&changes.block, Some(self.name.as_str()))
+                .await;
+            None
+        };
+
+        if let Err(err) = self
+            .stage_changes(changes, new_cursor)
+            .await
+        {
+            if let Some(config) = debug_bundle {
+                // drop the partially staged block, so the trace does not pick up the next block
+                self.state_gateway
+                    .discard_transaction()
+                    .await;
+                // nothing reached the database, the bundle still records the block and the error
+                let trace = WriteTrace::unsubmitted(&changes.block, Some(self.name.as_str()), &err);
+                self.write_debug_bundle(config, changes, trace)
+                    .await;
+            }
+            return Err(err);
+        }
+
+        // The traced block is committed on its own so the trace does not include later blocks.
+        let batch_size = if force_commit || trace_rx.is_some() { 0 } else { self.db_tx_batch_size };
+        let res = self
+            .state_gateway
+            .commit_transaction(batch_size)
             .await;
 
+        if let (Some(config), Some(trace_rx)) = (debug_bundle, trace_rx) {
+            match (trace_rx.await, &res) {
+                (Ok(trace), _) => {
+                    self.write_debug_bundle(config, changes, trace)
+                        .await
+                }
+                // the transaction never reached the write executor
+                (Err(_), Err(err)) => {
+                    let trace =
+                        WriteTrace::unsubmitted(&changes.block, Some(self.name.as_str()), err);
+                    self.write_debug_bundle(config, changes, trace)
+                        .await
+                }
+                (Err(_), Ok(())) => warn!(
+                    block_number = changes.block.number,
+                    "Database trace was dropped, no debug bundle written"
+                ),
+            }
+        }
+        res
+    }
+
+    /// Adds the writes of `changes` and the new cursor to the open transaction.
+    async fn stage_changes(
+        &self,
+        changes: &BlockChanges,
+        new_cursor: &str,
+    ) -> Result<(), StorageError> {
         // Insert new tokens
         if !changes.new_tokens.is_empty() {
             let new_tokens = changes
@@ -1476,6 +1578,13 @@ impl ExtractorGateway for ExtractorPgGateway {
                 .await?;
         }
 
+        // Skip changes of shared accounts already written by another extractor
+        if let Some(shared_accounts) = &self.shared_accounts {
+            shared_accounts
+                .claim_writes(&self.name, &changes.block, &mut account_changes)
+                .await;
+        }
+
         // Insert changed accounts
         if !account_changes.is_empty() {
             self.state_gateway
@@ -1525,14 +1634,99 @@ impl ExtractorGateway for ExtractorPgGateway {
                 .await?;
         }
 
+        // Insert fee accruals
+        if !changes.fee_accruals.is_empty() {
+            let fee_accruals: Vec<_> = changes
+                .fee_accruals
+                .values()
+                .flat_map(|accruals| accruals.values().cloned())
+                .collect();
+            self.state_gateway
+                .add_component_fee_accruals(fee_accruals.as_slice())
+                .await?;
+        }
+
         self.save_cursor(new_cursor, changes.block.hash.clone())
+            .await
+    }
+
+    #[instrument(skip_all)]
+    async fn save_cursor(
+        &self,
+        new_cursor: &str,
+        block_hash: BlockHash,
+    ) -> Result<(), StorageError> {
+        let state = ExtractionState::new(
+            self.name.to_string(),
+            self.chain,
+            None,
+            new_cursor.as_bytes(),
+            block_hash,
+        );
+        self.state_gateway
+            .save_state(&state)
             .await?;
+        Ok(())
+    }
 
-        let batch_size = if force_commit { 0 } else { self.db_tx_batch_size };
+    async fn get_last_extraction_state(&self) -> Result<ExtractionState, StorageError> {
+        let state = self
+            .state_gateway
+            .get_state(&self.name, &self.chain)
+            .await?;
+        Ok(state)
+    }
+}
+
+#[async_trait]
+impl ExtractorGateway for ExtractorPgGateway {
+    async fn get_block(&self, block_hash: Bytes) -> Result<Block, StorageError> {
         self.state_gateway
-            .commit_transaction(batch_size)
+            .get_block(&BlockIdentifier::Hash(block_hash))
             .await
     }
+    async fn get_cursor(&self) -> Result<(Vec<u8>, Bytes), StorageError> {
+        let extraction_state = self.get_last_extraction_state().await;
+        match extraction_state {
+            Ok(state) => Ok((state.cursor, state.block_hash)),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn ensure_protocol_types(&self, new_protocol_types: &[ProtocolType]) {
+        self.state_gateway
+            .add_protocol_types(new_protocol_types)
+            .await
+            .expect("Couldn't insert protocol types");
+    }
+
+    async fn advance(
+        &self,
+        changes: &BlockChanges,
+        new_cursor: &str,
+        force_commit: bool,
+    ) -> Result<(), StorageError> {
+        let res = self
+            .write_block(changes, new_cursor, force_commit)
+            .await;
+        if let Some(shared_accounts) = &self.shared_accounts {
+            if res.is_err() {
+                shared_accounts
+                    .discard_writes(&self.name)
+                    .await;
+            } else if !self
+                .state_gateway
+                .has_open_transaction()
+                .await
+            {
+                // writes of a batch are only confirmed once the batch was committed
+                shared_accounts
+                    .confirm_writes(&self.name)
+                    .await;
+            }
+        }
+        res
+    }
 
     async fn get_protocol_states<'a>(
         &self,
@@ -1579,6 +1773,7 @@ mod test {
     use super::*;
     use crate::{
         extractor::MockExtractorExtension,
+        pb::sf::substreams::v1::BlockRef,
         testing::{fixtures as pb_fixtures, MockGateway},
     };
 
@@ -1696,6 +1891,235 @@ mod test {
         assert_eq!(extractor.get_cursor().await, "cursor@2");
     }
 
+    type TestExtractor =
+        ProtocolExtractor<MockExtractorGateway, MockTokenPreProcessor, MockExtractorExtension>;
+
+    const SHARED_ACCOUNT: &str = "0x0000000000000000000000000000000000000001";
+
+    /// Creates an extractor subscribed to `SHARED_ACCOUNT`, with a component using it if `linked`.
+    async fn shared_account_extractor(
+        name: &str,
+        store: &SharedAccountStore,
+        linked: bool,
+    ) -> TestExtractor {
+        let shared_account = Bytes::from(SHARED_ACCOUNT);
+        store
+            .register(name, Chain::Ethereum, slice::from_ref(&shared_account))
+            .await;
+        let mut gw = MockExtractorGateway::new();
+        gw.expect_ensure_protocol_types()
+            .times(1)
+            .returning(|_| ());
+        gw.expect_get_cursor()
+            .times(1)
+            .returning(|| Ok(("cursor".into(), Bytes::default())));
+        gw.expect_get_block()
+            .times(1)
+            .returning(|_| Ok(Block::default()));
+        gw.expect_get_contracts()
+            .returning(|_| Ok(Vec::new()));
+        gw.expect_get_protocol_states()
+            .returning(|_| Ok(Vec::new()));
+        gw.expect_get_components_balances()
+            .returning(|_| Ok(HashMap::new()));
+        gw.expect_get_account_balances()
+            .returning(|_| Ok(HashMap::new()));
+        let mut extractor = create_extractor(gw).await;
+        extractor.name = name.to_string();
+        if linked {
+            extractor
+                .protocol_cache
+                .add_components([ProtocolComponent {
+                    id: format!("{name}_pool"),
+                    protocol_system: TEST_PROTOCOL.to_string(),
+                    contract_addresses: vec![shared_account],
+                    ..Default::default()
+                }])
+                .await
+                .unwrap();
+        }
+        extractor.with_shared_accounts(store.clone())
+    }
+
+    /// Block `number`, changing slot 1 of `SHARED_ACCOUNT` if `value` is given.
+    fn shared_account_block(number: u64, value: Option<u8>) -> tycho_substreams::BlockChanges {
+        let changes = value
+            .map(|value| tycho_substreams::TransactionChanges {
+                tx: Some(pb_fixtures::pb_transactions(number, 1)),
+                contract_changes: vec![tycho_substreams::ContractChange {
+                    address: Bytes::from(SHARED_ACCOUNT).to_vec(),
+                    slots: vec![tycho_substreams::ContractSlot {
+                        slot: vec![1],
+                        value: vec![value],
+                    }],
+                    change: tycho_substreams::ChangeType::Update.into(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            })
+            .into_iter()
+            .collect();
+        tycho_substreams::BlockChanges {
+            block: Some(pb_fixtures::pb_blocks(number)),
+            changes,
+            ..Default::default()
+        }
+    }
+
+    async fn handle_shared_account_block(
+        extractor: &TestExtractor,
+        number: u64,
+        value: Option<u8>,
+    ) -> ExtractorMsg {
+        extractor
+            .handle_tick_scoped_data(pb_fixtures::pb_block_scoped_data(
+                shared_account_block(number, value),
+                Some(format!("cursor@{number}").as_str()),
+                Some(1),
+            ))
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_handle_tick_scoped_data_shared_accounts() {
+        let shared_account = Bytes::from(SHARED_ACCOUNT);
+        let store = SharedAccountStore::new();
+        // only the first extractor indexes the shared account, the last one has no component
+        // using it
+        let publisher = shared_account_extractor("vm:a", &store, true).await;
+        let receiver = shared_account_extractor("vm:b", &store, true).await;
+        let unlinked = shared_account_extractor("vm:c", &store, false).await;
+
+        handle_shared_account_block(&publisher, 1, None).await;
+        let published = handle_shared_account_block(&publisher, 2, Some(2)).await;
+        let mut received = Vec::new();
+        let mut received_unlinked = Vec::new();
+        for number in 1..=3 {
+            received.push(handle_shared_account_block(&receiver, number, None).await);
+            received_unlinked.push(handle_shared_account_block(&unlinked, number, None).await);
+        }
+
+        assert!(published
+            .account_deltas
+            .contains_key(&shared_account));
+        // updates are delivered in the first message of a later block
+        assert!(received[1].account_deltas.is_empty());
+        assert_eq!(received[2].account_deltas, published.account_deltas);
+        assert!(received_unlinked
+            .iter()
+            .all(|msg| msg.account_deltas.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_handle_tick_scoped_data_shared_accounts_reverse_order() {
+        let store = SharedAccountStore::new();
+        let publisher = shared_account_extractor("vm:a", &store, true).await;
+        let receiver = shared_account_extractor("vm:b", &store, true).await;
+
+        // the receiver processes the block before the publisher
+        handle_shared_account_block(&receiver, 1, None).await;
+        let received_same_block = handle_shared_account_block(&receiver, 2, None).await;
+        handle_shared_account_block(&publisher, 1, None).await;
+        let published = handle_shared_account_block(&publisher, 2, Some(2)).await;
+        let received = handle_shared_account_block(&receiver, 3, None).await;
+
+        assert!(received_same_block
+            .account_deltas
+            .is_empty());
+        assert_eq!(received.account_deltas, published.account_deltas);
+    }
+
+    #[tokio::test]
+    async fn test_handle_revert_shared_accounts() {
+        let shared_account = Bytes::from(SHARED_ACCOUNT);
+        let store = SharedAccountStore::new();
+        let publisher = shared_account_extractor("vm:a", &store, true).await;
+        let receiver = shared_account_extractor("vm:b", &store, true).await;
+        handle_shared_account_block(&publisher, 1, None).await;
+        handle_shared_account_block(&publisher, 2, Some(2)).await;
+        for number in 1..=3 {
+            handle_shared_account_block(&receiver, number, None).await;
+        }
+
+        let revert = publisher
+            .handle_revert(BlockUndoSignal {
+                last_valid_block: Some(BlockRef {
+                    id: Bytes::from(1u64)
+                        .lpad(32, 0)
+                        .to_string(),
+                    number: 1,
+                }),
+                last_valid_cursor: "cursor@1".into(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        let received = handle_shared_account_block(&receiver, 4, None).await;
+
+        // the slot did not exist before the reverted block
+        assert_eq!(
+            revert.account_deltas[&shared_account].slots,
+            HashMap::from([(Bytes::from(vec![1u8]), Some(Bytes::new()))])
+        );
+        assert_eq!(received.account_deltas, revert.account_deltas);
+    }
+
+    #[tokio::test]
+    async fn test_handle_revert() {
+        let mut gw = MockExtractorGateway::new();
+        gw.expect_ensure_protocol_types()
+            .times(1)
+            .returning(|_| ());
+        gw.expect_get_cursor()
+            .times(1)
+            .returning(|| Ok(("cursor".into(), Bytes::default())));
+        gw.expect_get_block()
+            .times(1)
+            .returning(|_| Ok(Block::default()));
+        gw.expect_get_contracts()
+            .returning(|_| Ok(Vec::new()));
+        gw.expect_get_protocol_states()
+            .returning(|_| Ok(Vec::new()));
+        gw.expect_get_components_balances()
+            .returning(|_| Ok(HashMap::new()));
+        gw.expect_get_account_balances()
+            .returning(|_| Ok(HashMap::new()));
+        let extractor = create_extractor(gw).await;
+        for number in 1..=3 {
+            extractor
+                .handle_tick_scoped_data(pb_fixtures::pb_block_scoped_data(
+                    tycho_substreams::BlockChanges {
+                        block: Some(pb_fixtures::pb_blocks(number)),
+                        ..Default::default()
+                    },
+                    Some(format!("cursor@{number}").as_str()),
+                    Some(1),
+                ))
+                .await
+                .unwrap();
+        }
+
+        let msg = extractor
+            .handle_revert(BlockUndoSignal {
+                last_valid_block: Some(BlockRef {
+                    id: Bytes::from(2u64)
+                        .lpad(32, 0)
+                        .to_string(),
+                    number: 2,
+                }),
+                last_valid_cursor: "cursor@2".into(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(msg.revert);
+        assert_eq!(msg.block.number, 2);
+        assert_eq!(extractor.get_cursor().await, "cursor@2");
+    }
+
     #[tokio::test]
     async fn test_handle_tick_scoped_data_old_native_msg() {
         let mut gw = MockExtractorGateway::new();
@@ -2259,6 +2683,99 @@ mod test {
         assert_eq!(msg.component_tvl.len(), 1);
         assert_float_eq!(*res, exp_tvl, rmax <= 0.000_001);
     }
+
+    #[test_log::test(tokio::test)]
+    async fn test_handle_tvl_changes_native_token() {
+        let native_token = Chain::Ethereum.native_token();
+        let mut msg = BlockAggregatedChanges {
+            component_balances: HashMap::from([(
+                "comp1".to_string(),
+                HashMap::from([(
+                    native_token.address.clone(),
+                    ComponentBalance {
+                        token: native_token.address.clone(),
+                        balance: Bytes::from(2 * 10u128.pow(18)).lpad(32, 0),
+                        balance_float: 2e18,
+                        modify_tx: Bytes::zero(32),
+                        component_id: "comp1".to_string(),
+                    },
+                )]),
+            )]),
+            ..Default::default()
+        };
+
+        let mut protocol_gw = MockGateway::new();
+        protocol_gw
+            .expect_get_token_prices()
+            .return_once(|_| Box::pin(async { Ok(token_prices()) }));
+        let protocol_cache = ProtocolMemoryCache::new(
+            Chain::Ethereum,
+            chrono::Duration::seconds(1),
+            Arc::new(protocol_gw),
+        );
+        protocol_cache
+            .add_components([ProtocolComponent::new(
+                "comp1",
+                "system1",
+                "pt_1",
+                Chain::Ethereum,
+                vec![native_token.address.clone()],
+                Vec::new(),
+                HashMap::new(),
+                ChangeType::Creation,
+                Bytes::default(),
+                NaiveDateTime::default(),
+            )])
+            .await
+            .expect("adding components failed");
+        protocol_cache
+            .add_tokens([native_token])
+            .await
+            .expect("adding tokens failed");
+
+        let mut extractor_gw = MockExtractorGateway::new();
+        extractor_gw
+            .expect_ensure_protocol_types()
+            .times(1)
+            .returning(|_| ());
+        extractor_gw
+            .expect_get_cursor()
+            .times(1)
+            .returning(|| Ok(("cursor".into(), Bytes::default())));
+        extractor_gw
+            .expect_get_components_balances()
+            .return_once(|_| Ok(HashMap::new()));
+        extractor_gw
+            .expect_get_block()
+            .times(1)
+            .returning(|_| Ok(Block::default()));
+
+        let extractor = ProtocolExtractor::<
+            MockExtractorGateway,
+            MockTokenPreProcessor,
+            MockExtractorExtension,
+        >::new(
+            extractor_gw,
+            "vm_name",
+            Chain::Ethereum,
+            ChainState::default(),
+            "system1".to_string(),
+            protocol_cache,
+            HashMap::from([("pt_1".to_string(), ProtocolType::default())]),
+            MockTokenPreProcessor::new(),
+            None,
+            None,
+        )
+        .await
+        .expect("extractor init failed");
+
+        extractor
+            .handle_tvl_changes(&mut msg)
+            .await
+            .expect("handle_tvl_call failed");
+
+        assert_eq!(msg.component_tvl, HashMap::from([("comp1".to_string(), 2.0)]));
+    }
 }
 
 /// It is notoriously hard to mock postgres here, we would need to have traits and abstractions
@@ -7,7 +7,7 @@ use tycho_common::{
         blockchain::{Block, BlockScoped},
         contract::AccountBalance,
         protocol::ComponentBalance,
-        Address, AttrStoreKey, ComponentId, StoreVal,
+        Address, AttrStoreKey, BlockHash, ComponentId, StoreVal,
     },
     storage::{BlockIdentifier, BlockOrTimestamp, StorageError},
     Bytes,
@@ -57,9 +57,15 @@ impl TryFrom<BlockOrTimestamp> for BlockNumberOrTimestamp {
 /// In case of a chain reorg, we can just purge this buffer.
 pub(crate) struct ReorgBuffer<B: BlockScoped> {
     block_messages: VecDeque<B>,
+    /// Hashes of the most recently purged blocks, used to tell apart orphaned blocks from blocks
+    /// we have never seen. Bounded by `MAX_ORPHANED_HASHES`.
+    orphaned_hashes: VecDeque<BlockHash>,
     strict: bool,
 }
 
+/// Maximum number of orphaned block hashes remembered by a `ReorgBuffer`.
+const MAX_ORPHANED_HASHES: usize = 256;
+
 /// The finality status of a block or block-scoped data.
 #[derive(PartialEq, Clone, Debug, Copy)]
 pub enum FinalityStatus {
@@ -76,7 +82,7 @@ where
     B: BlockScoped + std::fmt::Debug,
 {
     pub(crate) fn new() -> Self {
-        Self { block_messages: VecDeque::new(), strict: false }
+        Self { block_messages: VecDeque::new(), orphaned_hashes: VecDeque::new(), strict: false }
     }
 
     /// Inserts a new block into the buffer. Ensures the new block is the expected next block,
@@ -93,6 +99,10 @@ where
             };
         }
 
+        // A block may be orphaned and later become canonical again, e.g. on a reorg of a reorg.
+        let hash = new.block().hash;
+        self.orphaned_hashes
+            .retain(|orphaned| orphaned != &hash);
         self.block_messages.push_back(new);
 
         Ok(())
@@ -152,17 +162,30 @@ where
         }
 
         if let Some(idx) = target_index {
-            let purged = self
+            let purged: Vec<B> = self
                 .block_messages
                 .split_off(idx)
                 .into();
             trace!(?purged, "ReorgBuffer purged blocks");
+            for block_message in purged.iter() {
+                if self.orphaned_hashes.len() == MAX_ORPHANED_HASHES {
+                    self.orphaned_hashes.pop_front();
+                }
+                self.orphaned_hashes
+                    .push_back(block_message.block().hash);
+            }
             Ok(purged)
         } else {
             Err(StorageError::NotFound("block".into(), target_hash.to_string()))
         }
     }
 
+    /// Returns true if the block with the given hash was recently purged from the buffer, i.e. it
+    /// was orphaned by a reorg and has not become part of the canonical chain again.
+    pub fn is_orphaned(&self, hash: &BlockHash) -> bool {
+        self.orphaned_hashes.contains(hash)
+    }
+
     /// Returns an `Option` containing the most recent block in the buffer or `None` if the buffer
     /// is empty
     pub fn get_most_recent_block(&self) -> Option<tycho_common::models::blockchain::Block> {
@@ -908,6 +931,36 @@ mod test {
         assert!(unknown.is_err());
     }
 
+    #[test]
+    fn test_purge_tracks_orphaned_blocks() {
+        let mut reorg_buffer = ReorgBuffer::new();
+        reorg_buffer
+            .insert_block(get_block_changes(1))
+            .unwrap();
+        reorg_buffer
+            .insert_block(get_block_changes(2))
+            .unwrap();
+        reorg_buffer
+            .insert_block(get_block_changes(3))
+            .unwrap();
+
+        reorg_buffer
+            .purge(get_block_changes(1).block.hash)
+            .unwrap();
+
+        assert!(!reorg_buffer.is_orphaned(&get_block_changes(1).block.hash));
+        assert!(reorg_buffer.is_orphaned(&get_block_changes(2).block.hash));
+        assert!(reorg_buffer.is_orphaned(&get_block_changes(3).block.hash));
+
+        // Re-inserting a previously orphaned block makes it canonical again.
+        reorg_buffer
+            .insert_block(get_block_changes(2))
+            .unwrap();
+
+        assert!(!reorg_buffer.is_orphaned(&get_block_changes(2).block.hash));
+        assert!(reorg_buffer.is_orphaned(&get_block_changes(3).block.hash));
+    }
+
     #[test]
     #[should_panic]
     fn test_insert_wrong_block() {
@@ -31,10 +31,12 @@ use tycho_storage::postgres::cache::CachedGateway;
 use crate::{
     extractor::{
         chain_state::ChainState,
+        debug_bundle::DebugBundleConfig,
         dynamic_contract_indexer::dci::DynamicContractIndexer,
         post_processors::POST_PROCESSOR_REGISTRY,
         protocol_cache::ProtocolMemoryCache,
         protocol_extractor::{ExtractorPgGateway, ProtocolExtractor},
+        shared_accounts::SharedAccountStore,
         ExtractionError, Extractor, ExtractorMsg,
     },
     pb::sf::substreams::v1::Package,
@@ -328,6 +330,13 @@ pub struct ExtractorConfig {
     pub post_processor: Option<String>,
     #[serde(default)]
     pub dci_plugin: Option<DCIType>,
+    /// Writes a debug bundle with the traced write operations of a single block.
+    #[serde(default)]
+    pub debug_bundle: Option<DebugBundleConfig>,
+    /// Contracts shared with other extractors of the chain, e.g. WETH. Their changes are written
+    /// only once and fanned out to all extractors referencing them.
+    #[serde(default)]
+    pub shared_accounts: Vec<Bytes>,
 }
 
 impl ExtractorConfig {
@@ -361,8 +370,18 @@ impl ExtractorConfig {
             initialized_accounts_block,
             post_processor,
             dci_plugin,
+            debug_bundle: None,
+            shared_accounts: Vec::new(),
         }
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn chain(&self) -> Chain {
+        self.chain
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -383,6 +402,8 @@ pub struct ExtractorBuilder {
     runtime_handle: Option<Handle>,
     /// Global RPC URL to use for DCI plugins
     rpc_url: Option<String>,
+    /// Store of the accounts shared with the other extractors of the chain.
+    shared_accounts: Option<SharedAccountStore>,
 }
 
 pub type HandleResult = (JoinHandle<Result<(), ExtractionError>>, ExtractorHandle);
@@ -398,6 +419,7 @@ impl ExtractorBuilder {
             final_block_only: false,
             runtime_handle: None,
             rpc_url: None,
+            shared_accounts: None,
         }
     }
 
@@ -438,6 +460,12 @@ impl ExtractorBuilder {
         self
     }
 
+    /// Set the store of the accounts shared with the other extractors of the chain
+    pub fn shared_accounts(mut self, store: &SharedAccountStore) -> Self {
+        self.shared_accounts = Some(store.clone());
+        self
+    }
+
     #[cfg(test)]
     pub fn set_extractor(mut self, val: Arc<dyn Extractor>) -> Self {
         self.extractor = Some(val);
@@ -492,12 +520,25 @@ impl ExtractorBuilder {
             })
             .collect();
 
-        let gw = ExtractorPgGateway::new(
+        let mut gw = ExtractorPgGateway::new(
             &self.config.name,
             self.config.chain,
             self.config.sync_batch_size,
             cached_gw.clone(),
         );
+        if let Some(debug_bundle) = self.config.debug_bundle.clone() {
+            gw = gw.with_debug_bundle(debug_bundle);
+        }
+        let shared_accounts = match &self.shared_accounts {
+            Some(store) if !self.config.shared_accounts.is_empty() => {
+                store
+                    .register(&self.config.name, self.config.chain, &self.config.shared_accounts)
+                    .await;
+                gw = gw.with_shared_accounts(store.clone());
+                Some(store.clone())
+            }
+            _ => None,
+        };
 
         let post_processor = self
             .config
@@ -552,29 +593,27 @@ impl ExtractorBuilder {
             None
         };
 
-        self.extractor = Some(Arc::new(
-            ProtocolExtractor::<
-                ExtractorPgGateway,
-                EthereumTokenPreProcessor,
-                DynamicContractIndexer<
-                    EVMBatchAccountExtractor,
-                    EVMEntrypointService,
-                    CachedGateway,
-                >,
-            >::new(
-                gw,
-                &self.config.name,
-                self.config.chain,
-                chain_state,
-                self.config.name.clone(),
-                protocol_cache.clone(),
-                protocol_types,
-                token_pre_processor.clone(),
-                post_processor,
-                dci_plugin,
-            )
-            .await?,
-        ));
+        let mut extractor = ProtocolExtractor::<
+            ExtractorPgGateway,
+            EthereumTokenPreProcessor,
+            DynamicContractIndexer<EVMBatchAccountExtractor, EVMEntrypointService, CachedGateway>,
+        >::new(
+            gw,
+            &self.config.name,
+            self.config.chain,
+            chain_state,
+            self.config.name.clone(),
+            protocol_cache.clone(),
+            protocol_types,
+            token_pre_processor.clone(),
+            post_processor,
+            dci_plugin,
+        )
+        .await?;
+        if let Some(store) = shared_accounts {
+            extractor = extractor.with_shared_accounts(store);
+        }
+        self.extractor = Some(Arc::new(extractor));
 
         Ok(self)
     }
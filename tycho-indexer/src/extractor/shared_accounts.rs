@@ -0,0 +1,608 @@
+//! Contracts shared by multiple extractors.
+//!
+//! Many VM protocols index the same contracts, e.g. WETH, permit2 or oracles. Accounts are stored
+//! once per chain already, but without coordination every extractor indexing such a contract
+//! writes the same changes again and only its own subscribers learn about them.
+//!
+//! Extractors list the shared contracts they reference in their config (`shared_accounts`) and
+//! subscribe to them in the [`SharedAccountStore`]. Accounts are identified by chain and address,
+//! so extractors of different chains never share an account. The store then:
+//!
+//! - deduplicates writes: the changes of a shared account within a block are written by the first
+//!   subscriber that commits this block, other subscribers skip the slots, balance and code it
+//!   wrote with identical values. Writes are only considered written once the subscriber's database
+//!   transaction committed, until then other subscribers write them as well.
+//! - fans out updates: changes an extractor observes at the chain head are merged into the delta
+//!   messages of all other subscribers, so their clients receive them even if their own substreams
+//!   package does not index the contract. Updates of a block are merged into the subscriber's first
+//!   message of a later block, independent of which extractor processes the block first. Clients
+//!   therefore receive fanned out updates at least one block late.
+//!
+//! Subscribers may index a different subset of a shared account's state, changes that differ from
+//! the written ones are always written.
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+};
+
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+use tycho_common::models::{
+    blockchain::Block, contract::AccountDelta, Address, Balance, BlockHash, Chain, Code, StoreKey,
+    StoreVal, TxHash,
+};
+
+/// Maximum number of blocks of updates kept for a subscriber that did not emit a message since.
+const MAX_PENDING_BLOCKS: usize = 128;
+/// Maximum number of written blocks remembered per account to deduplicate writes.
+const MAX_WRITTEN_BLOCKS: usize = 10_000;
+
+/// Identifies a shared account across chains.
+type AccountKey = (Chain, Address);
+
+/// An account update waiting to be merged into a subscriber's message.
+#[derive(Debug, Clone)]
+struct PendingUpdate {
+    publisher: String,
+    delta: AccountDelta,
+}
+
+/// Changes of an account within a block that were written to the database, with their last
+/// written values.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct WrittenChanges {
+    block_hash: BlockHash,
+    slots: HashMap<StoreKey, Option<StoreVal>>,
+    balance: Option<Balance>,
+    code: Option<Code>,
+}
+
+impl WrittenChanges {
+    fn new(block_hash: BlockHash) -> Self {
+        Self { block_hash, ..Default::default() }
+    }
+
+    /// Removes the changes that were written with identical values from `delta`, returns whether
+    /// it still contains changes.
+    fn retain_unwritten(&self, delta: &mut AccountDelta) -> bool {
+        delta
+            .slots
+            .retain(|slot, value| self.slots.get(slot) != Some(value));
+        if delta.balance.is_some() && delta.balance == self.balance {
+            delta.balance = None;
+        }
+        if delta.code.is_some() && delta.code == self.code {
+            delta.code = None;
+        }
+        !delta.slots.is_empty() || delta.balance.is_some() || delta.code.is_some()
+    }
+
+    fn add_delta(&mut self, delta: &AccountDelta) {
+        self.slots.extend(delta.slots.clone());
+        if delta.balance.is_some() {
+            self.balance = delta.balance.clone();
+        }
+        if delta.code.is_some() {
+            self.code = delta.code.clone();
+        }
+    }
+
+    fn extend(&mut self, other: WrittenChanges) {
+        self.slots.extend(other.slots);
+        if other.balance.is_some() {
+            self.balance = other.balance;
+        }
+        if other.code.is_some() {
+            self.code = other.code;
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// Extractors subscribed to each shared account.
+    subscribers: HashMap<AccountKey, HashSet<String>>,
+    /// Updates by block number, waiting to be merged into each subscriber's next message.
+    pending: HashMap<String, BTreeMap<u64, Vec<PendingUpdate>>>,
+    /// Last block in which an extractor emitted changes of a shared account itself.
+    own_updates: HashMap<(String, Address), u64>,
+    /// Changes of each account that were written, by block number.
+    written: HashMap<AccountKey, BTreeMap<u64, WrittenChanges>>,
+    /// Changes each subscriber staged for writing but did not commit yet.
+    staged: HashMap<String, HashMap<(AccountKey, u64), WrittenChanges>>,
+    /// Last block committed by each subscriber.
+    progress: HashMap<String, u64>,
+}
+
+/// Registry of shared accounts, shared by all extractors.
+#[derive(Clone, Default)]
+pub struct SharedAccountStore {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl SharedAccountStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes `extractor`, which indexes `chain`, to the given shared accounts.
+    pub async fn register(&self, extractor: &str, chain: Chain, accounts: &[Address]) {
+        let mut inner = self.inner.lock().await;
+        for address in accounts {
+            inner
+                .subscribers
+                .entry((chain, address.clone()))
+                .or_default()
+                .insert(extractor.to_string());
+        }
+        debug!(extractor, %chain, n_accounts = accounts.len(), "Registered shared accounts");
+    }
+
+    /// Publishes the account changes `extractor` emits for `block` to the other subscribers.
+    ///
+    /// Changes of accounts `extractor` is not subscribed to are ignored.
+    pub async fn publish(
+        &self,
+        extractor: &str,
+        block: &Block,
+        deltas: &HashMap<Address, AccountDelta>,
+    ) {
+        let mut inner = self.inner.lock().await;
+        let mut receivers = HashSet::new();
+        for (address, delta) in deltas.iter() {
+            let Some(subscribers) = inner
+                .subscribers
+                .get(&(block.chain, address.clone()))
+            else {
+                continue;
+            };
+            if !subscribers.contains(extractor) {
+                continue;
+            }
+            let others: Vec<_> = subscribers
+                .iter()
+                .filter(|name| name.as_str() != extractor)
+                .cloned()
+                .collect();
+            inner
+                .own_updates
+                .insert((extractor.to_string(), address.clone()), block.number);
+            for other in others {
+                inner
+                    .pending
+                    .entry(other.clone())
+                    .or_default()
+                    .entry(block.number)
+                    .or_default()
+                    .push(PendingUpdate { publisher: extractor.to_string(), delta: delta.clone() });
+                receivers.insert(other);
+            }
+        }
+
+        for receiver in receivers {
+            let Some(queue) = inner.pending.get_mut(&receiver) else {
+                continue;
+            };
+            while queue.len() > MAX_PENDING_BLOCKS {
+                if let Some((dropped, _)) = queue.pop_first() {
+                    warn!(
+                        extractor = receiver,
+                        block_number = dropped,
+                        "Dropped shared account updates of a subscriber that fell behind"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Publishes a revert of `extractor` to `block`.
+    ///
+    /// Updates of the reverted blocks that were not yet merged are discarded, `deltas` are the
+    /// account states restored by the revert.
+    pub async fn publish_revert(
+        &self,
+        extractor: &str,
+        block: &Block,
+        deltas: &HashMap<Address, AccountDelta>,
+    ) {
+        {
+            let mut inner = self.inner.lock().await;
+            for queue in inner.pending.values_mut() {
+                for (_, updates) in queue.range_mut(block.number + 1..) {
+                    updates.retain(|update| update.publisher != extractor);
+                }
+                queue.retain(|_, updates| !updates.is_empty());
+            }
+            for ((name, _), number) in inner.own_updates.iter_mut() {
+                if name == extractor {
+                    *number = (*number).min(block.number);
+                }
+            }
+        }
+        self.publish(extractor, block, deltas)
+            .await;
+    }
+
+    /// Takes the updates published to `extractor` for blocks before `block`, merged per account.
+    ///
+    /// Updates of `block` itself are kept for the next call, so they are delivered in the same
+    /// message no matter whether `extractor` processes `block` before or after the publisher.
+    /// Updates of accounts `extractor` emitted changes for itself in the same or a later block are
+    /// skipped, as its own changes are at least as recent.
+    pub async fn take_updates(
+        &self,
+        extractor: &str,
+        block: &Block,
+    ) -> HashMap<Address, AccountDelta> {
+        let mut inner = self.inner.lock().await;
+        let Some(queue) = inner.pending.get_mut(extractor) else {
+            return HashMap::new();
+        };
+        let later = queue.split_off(&block.number);
+        let due = std::mem::replace(queue, later);
+
+        let mut merged: HashMap<Address, AccountDelta> = HashMap::new();
+        for (number, updates) in due {
+            for update in updates {
+                let address = update.delta.address.clone();
+                let is_outdated = inner
+                    .own_updates
+                    .get(&(extractor.to_string(), address.clone()))
+                    .is_some_and(|own| *own >= number);
+                if is_outdated {
+                    continue;
+                }
+                match merged.get_mut(&address) {
+                    Some(delta) => delta
+                        .merge(update.delta)
+                        .expect("deltas are merged by address"),
+                    None => {
+                        merged.insert(address, update.delta);
+                    }
+                }
+            }
+        }
+        merged
+    }
+
+    /// Claims the writes of `extractor` for the account changes of `block`.
+    ///
+    /// Removes the changes of shared accounts other subscribers already wrote for this block from
+    /// `changes`, all other changes are kept. The remaining changes of shared accounts are staged
+    /// and only considered written once confirmed with [`Self::confirm_writes`].
+    pub async fn claim_writes(
+        &self,
+        extractor: &str,
+        block: &Block,
+        changes: &mut Vec<(TxHash, AccountDelta)>,
+    ) {
+        let mut inner = self.inner.lock().await;
+        let Inner { subscribers, written, staged, .. } = &mut *inner;
+        let staged = staged
+            .entry(extractor.to_string())
+            .or_default();
+        changes.retain_mut(|(_, delta)| {
+            let key = (block.chain, delta.address.clone());
+            let is_subscribed = subscribers
+                .get(&key)
+                .is_some_and(|subscribers| subscribers.contains(extractor));
+            if !is_subscribed {
+                return true;
+            }
+            let is_unwritten = match written
+                .get(&key)
+                .and_then(|blocks| blocks.get(&block.number))
+            {
+                Some(changes) if changes.block_hash == block.hash => {
+                    changes.retain_unwritten(delta)
+                }
+                _ => true,
+            };
+            if !is_unwritten {
+                debug!(
+                    extractor,
+                    address = ?delta.address,
+                    block_number = block.number,
+                    "Skip shared account write"
+                );
+                return false;
+            }
+            staged
+                .entry((key, block.number))
+                .or_insert_with(|| WrittenChanges::new(block.hash.clone()))
+                .add_delta(delta);
+            true
+        });
+    }
+
+    /// Confirms the writes `extractor` claimed so far, after its database transaction committed.
+    pub async fn confirm_writes(&self, extractor: &str) {
+        let mut inner = self.inner.lock().await;
+        let Some(staged) = inner.staged.remove(extractor) else {
+            return;
+        };
+        let Inner { subscribers, written, progress, .. } = &mut *inner;
+        for ((key, number), changes) in staged {
+            let blocks = written.entry(key).or_default();
+            match blocks.get_mut(&number) {
+                Some(current) if current.block_hash == changes.block_hash => {
+                    current.extend(changes)
+                }
+                _ => {
+                    blocks.insert(number, changes);
+                }
+            }
+            while blocks.len() > MAX_WRITTEN_BLOCKS {
+                blocks.pop_first();
+            }
+            let last = progress
+                .entry(extractor.to_string())
+                .or_default();
+            *last = (*last).max(number);
+        }
+
+        // blocks all subscribers have committed can't be claimed anymore
+        for (key, blocks) in written.iter_mut() {
+            let min_progress = subscribers
+                .get(key)
+                .into_iter()
+                .flatten()
+                .map(|name| progress.get(name).copied())
+                .min()
+                .flatten();
+            if let Some(min_progress) = min_progress {
+                *blocks = blocks.split_off(&(min_progress + 1));
+            }
+        }
+    }
+
+    /// Discards the writes `extractor` claimed since its last confirmation, e.g. because its
+    /// database transaction failed.
+    pub async fn discard_writes(&self, extractor: &str) {
+        let mut inner = self.inner.lock().await;
+        if let Some(staged) = inner.staged.remove(extractor) {
+            debug!(extractor, n_claims = staged.len(), "Discarded shared account writes");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDateTime;
+    use tycho_common::{
+        models::{Chain, ChangeType},
+        Bytes,
+    };
+
+    use super::*;
+
+    const WETH: &str = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+
+    fn block(number: u64) -> Block {
+        chain_block(Chain::Ethereum, number)
+    }
+
+    fn chain_block(chain: Chain, number: u64) -> Block {
+        Block::new(
+            number,
+            chain,
+            Bytes::from(number).lpad(32, 0),
+            Bytes::from(number - 1).lpad(32, 0),
+            NaiveDateTime::default(),
+        )
+    }
+
+    fn delta(slot: u8, value: u8) -> AccountDelta {
+        AccountDelta::new(
+            Chain::Ethereum,
+            Bytes::from(WETH),
+            HashMap::from([(Bytes::from(slot), Some(Bytes::from(value)))]),
+            None,
+            None,
+            ChangeType::Update,
+        )
+    }
+
+    fn deltas(slot: u8, value: u8) -> HashMap<Address, AccountDelta> {
+        HashMap::from([(Bytes::from(WETH), delta(slot, value))])
+    }
+
+    async fn store() -> SharedAccountStore {
+        let store = SharedAccountStore::new();
+        for extractor in ["vm:a", "vm:b"] {
+            store
+                .register(extractor, Chain::Ethereum, &[Bytes::from(WETH)])
+                .await;
+        }
+        store
+    }
+
+    #[tokio::test]
+    async fn test_take_updates() {
+        let store = store().await;
+        store
+            .publish("vm:a", &block(1), &deltas(1, 1))
+            .await;
+        store
+            .publish("vm:a", &block(2), &deltas(1, 2))
+            .await;
+        store
+            .publish("vm:a", &block(3), &deltas(2, 3))
+            .await;
+
+        let res = store
+            .take_updates("vm:b", &block(3))
+            .await;
+
+        assert_eq!(res, deltas(1, 2));
+        assert!(store
+            .take_updates("vm:a", &block(4))
+            .await
+            .is_empty());
+        assert_eq!(
+            store
+                .take_updates("vm:b", &block(4))
+                .await,
+            deltas(2, 3)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_take_updates_skips_outdated() {
+        let store = store().await;
+        store
+            .publish("vm:a", &block(1), &deltas(1, 1))
+            .await;
+        store
+            .publish("vm:b", &block(2), &deltas(1, 2))
+            .await;
+
+        let res = store
+            .take_updates("vm:b", &block(3))
+            .await;
+
+        assert!(res.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_publish_revert() {
+        let store = store().await;
+        store
+            .publish("vm:a", &block(1), &deltas(1, 1))
+            .await;
+        store
+            .publish("vm:a", &block(2), &deltas(1, 2))
+            .await;
+        store
+            .publish("vm:a", &block(3), &deltas(1, 3))
+            .await;
+
+        store
+            .publish_revert("vm:a", &block(1), &deltas(1, 1))
+            .await;
+        let res = store
+            .take_updates("vm:b", &block(4))
+            .await;
+
+        assert_eq!(res, deltas(1, 1));
+    }
+
+    fn changes(slots: &[u8]) -> Vec<(TxHash, AccountDelta)> {
+        let mut shared = delta(slots[0], 1);
+        for slot in &slots[1..] {
+            shared
+                .slots
+                .insert(Bytes::from(*slot), Some(Bytes::from(1u8)));
+        }
+        vec![
+            (Bytes::from("0x01"), shared),
+            (Bytes::from("0x01"), AccountDelta { address: Bytes::from("0x02"), ..delta(1, 1) }),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_claim_writes() {
+        let store = store().await;
+        let mut changes_a = changes(&[1]);
+        let mut changes_b = changes(&[1, 2]);
+        let mut changes_b_next = changes(&[1]);
+
+        store
+            .claim_writes("vm:a", &block(1), &mut changes_a)
+            .await;
+        store.confirm_writes("vm:a").await;
+        store
+            .claim_writes("vm:b", &block(1), &mut changes_b)
+            .await;
+        store
+            .claim_writes("vm:b", &block(2), &mut changes_b_next)
+            .await;
+
+        assert_eq!(changes_a, changes(&[1]));
+        // only the slot vm:a did not write is left
+        assert_eq!(
+            changes_b[0].1.slots,
+            HashMap::from([(Bytes::from(2u8), Some(Bytes::from(1u8)))])
+        );
+        assert_eq!(changes_b[1], changes(&[1])[1]);
+        assert_eq!(changes_b_next, changes(&[1]));
+    }
+
+    #[tokio::test]
+    async fn test_claim_writes_unconfirmed() {
+        let store = store().await;
+        let mut changes_a = changes(&[1]);
+        let mut changes_b = changes(&[1]);
+        let mut changes_b_retry = changes(&[1]);
+
+        store
+            .claim_writes("vm:a", &block(1), &mut changes_a)
+            .await;
+        // writes are only skipped once they were committed
+        store
+            .claim_writes("vm:b", &block(1), &mut changes_b)
+            .await;
+        // the transaction of vm:a failed
+        store.discard_writes("vm:a").await;
+        store.confirm_writes("vm:a").await;
+        store
+            .claim_writes("vm:b", &block(1), &mut changes_b_retry)
+            .await;
+
+        assert_eq!(changes_b, changes(&[1]));
+        assert_eq!(changes_b_retry, changes(&[1]));
+    }
+
+    #[tokio::test]
+    async fn test_claim_writes_different_values() {
+        let store = store().await;
+        let mut changes_a = changes(&[1]);
+        let mut changes_b = changes(&[1]);
+        // vm:b indexes a different value for the same slot
+        changes_b[0]
+            .1
+            .slots
+            .insert(Bytes::from(1u8), Some(Bytes::from(2u8)));
+
+        store
+            .claim_writes("vm:a", &block(1), &mut changes_a)
+            .await;
+        store.confirm_writes("vm:a").await;
+        let expected = changes_b.clone();
+        store
+            .claim_writes("vm:b", &block(1), &mut changes_b)
+            .await;
+
+        assert_eq!(changes_b, expected);
+    }
+
+    #[tokio::test]
+    async fn test_chains_do_not_share_accounts() {
+        let store = SharedAccountStore::new();
+        store
+            .register("vm:a", Chain::Ethereum, &[Bytes::from(WETH)])
+            .await;
+        store
+            .register("vm:b", Chain::Base, &[Bytes::from(WETH)])
+            .await;
+        let mut changes_a = changes(&[1]);
+        let mut changes_b = changes(&[1]);
+
+        store
+            .publish("vm:a", &chain_block(Chain::Ethereum, 1), &deltas(1, 1))
+            .await;
+        store
+            .claim_writes("vm:a", &chain_block(Chain::Ethereum, 1), &mut changes_a)
+            .await;
+        store.confirm_writes("vm:a").await;
+        store
+            .claim_writes("vm:b", &chain_block(Chain::Base, 1), &mut changes_b)
+            .await;
+
+        assert!(store
+            .take_updates("vm:b", &chain_block(Chain::Base, 2))
+            .await
+            .is_empty());
+        assert_eq!(changes_b, changes(&[1]));
+    }
+}
@@ -4,6 +4,7 @@ use std::{
     env,
     fs::File,
     io::Read,
+    path::PathBuf,
     process, slice,
     str::FromStr,
     sync::{mpsc, Arc},
@@ -38,14 +39,16 @@ use tycho_ethereum::{
     token_analyzer::rpc_client::EthereumRpcClient, token_pre_processor::EthereumTokenPreProcessor,
 };
 use tycho_indexer::{
-    cli::{AnalyzeTokenArgs, Cli, Command, GlobalArgs, IndexArgs, RunSpkgArgs},
+    cli::{AnalyzeTokenArgs, Cli, Command, GlobalArgs, IndexArgs, RpcArgs, RunSpkgArgs},
     extractor::{
         chain_state::ChainState,
+        debug_bundle::DebugBundleConfig,
         protocol_cache::ProtocolMemoryCache,
         runner::{
             DCIType, ExtractorBuilder, ExtractorConfig, ExtractorHandle, HandleResult,
             ProtocolTypeConfig,
         },
+        shared_accounts::SharedAccountStore,
         token_analysis_cron::analyze_tokens,
         ExtractionError,
     },
@@ -93,7 +96,7 @@ fn main() {
         Command::AnalyzeTokens(analyze_args) => {
             run_tycho_ethereum(global_args, analyze_args).unwrap();
         }
-        Command::Rpc => run_rpc(global_args).unwrap(),
+        Command::Rpc(rpc_args) => run_rpc(global_args, rpc_args).unwrap(),
     }
 }
 
@@ -195,10 +198,26 @@ fn run_indexer(global_args: GlobalArgs, index_args: IndexArgs) -> Result<(), Ext
 
             info!("Starting Tycho");
             debug!("{} CPUs detected", num_cpus::get());
-            let extractors_config = ExtractorConfigs::from_yaml(&index_args.extractors_config)
+            let mut extractors_config = ExtractorConfigs::from_yaml(&index_args.extractors_config)
                 .map_err(|e| {
                     ExtractionError::Setup(format!("Failed to load extractors.yaml. {e}"))
                 })?;
+            if let (Some(name), Some(block)) =
+                (&index_args.debug_bundle_extractor, index_args.debug_bundle_block)
+            {
+                let extractor_config = extractors_config
+                    .extractors
+                    .get_mut(name)
+                    .ok_or_else(|| {
+                        ExtractionError::Setup(format!(
+                            "Debug bundle extractor {name} is not configured"
+                        ))
+                    })?;
+                extractor_config.debug_bundle = Some(DebugBundleConfig::new(
+                    block,
+                    PathBuf::from(&index_args.debug_bundle_dir),
+                ));
+            }
 
             let retention_horizon: NaiveDateTime = index_args
                 .retention_horizon
@@ -207,14 +226,7 @@ fn run_indexer(global_args: GlobalArgs, index_args: IndexArgs) -> Result<(), Ext
 
             let (extraction_tasks, other_tasks) = create_indexing_tasks(
                 &global_args,
-                &index_args
-                    .chains
-                    .iter()
-                    .map(|chain_str| {
-                        Chain::from_str(chain_str)
-                            .unwrap_or_else(|_| panic!("Unknown chain {chain_str}"))
-                    })
-                    .collect::<Vec<_>>(),
+                &parse_chains(&index_args.chains)?,
                 retention_horizon,
                 extractors_config,
                 Some(extraction_runtime.handle()),
@@ -267,31 +279,31 @@ async fn run_spkg(global_args: GlobalArgs, run_args: RunSpkgArgs) -> Result<(),
             _ => Err(ExtractionError::Setup(format!("Unknown DCI plugin: {s}"))),
         })?;
 
-    let config = ExtractorConfigs::new(HashMap::from([(
+    let mut extractor_config = ExtractorConfig::new(
         "test_protocol".to_string(),
-        ExtractorConfig::new(
-            "test_protocol".to_string(),
-            Chain::from_str(&run_args.chain).unwrap(),
-            ImplementationType::Vm,
-            1, /* TODO: if we want to increase this, we need to commit the cache when we reached
-                * `end_block` */
-            run_args.start_block,
-            run_args.stop_block(),
-            run_args
-                .protocol_type_names
-                .into_iter()
-                .map(|name| {
-                    ProtocolTypeConfig::new(name, tycho_common::models::FinancialType::Swap)
-                })
-                .collect::<Vec<_>>(),
-            run_args.spkg,
-            run_args.module,
-            run_args.initialized_accounts,
-            run_args.initialization_block,
-            None,
-            dci_plugin,
-        ),
-    )]));
+        Chain::from_str(&run_args.chain).unwrap(),
+        ImplementationType::Vm,
+        1, /* TODO: if we want to increase this, we need to commit the cache when we reached
+            * `end_block` */
+        run_args.start_block,
+        run_args.stop_block(),
+        run_args
+            .protocol_type_names
+            .into_iter()
+            .map(|name| ProtocolTypeConfig::new(name, tycho_common::models::FinancialType::Swap))
+            .collect::<Vec<_>>(),
+        run_args.spkg,
+        run_args.module,
+        run_args.initialized_accounts,
+        run_args.initialization_block,
+        None,
+        dci_plugin,
+    );
+    extractor_config.debug_bundle = run_args
+        .debug_bundle_block
+        .map(|block| DebugBundleConfig::new(block, PathBuf::from(run_args.debug_bundle_dir)));
+    let config =
+        ExtractorConfigs::new(HashMap::from([("test_protocol".to_string(), extractor_config)]));
 
     let (extraction_tasks, mut other_tasks) = create_indexing_tasks(
         &global_args,
@@ -310,11 +322,12 @@ async fn run_spkg(global_args: GlobalArgs, run_args: RunSpkgArgs) -> Result<(),
 }
 
 #[tokio::main]
-async fn run_rpc(global_args: GlobalArgs) -> Result<(), ExtractionError> {
+async fn run_rpc(global_args: GlobalArgs, rpc_args: RpcArgs) -> Result<(), ExtractionError> {
     create_tracing_subscriber();
 
+    let chains = parse_chains(&rpc_args.chains)?;
     let direct_gw = GatewayBuilder::new(&global_args.database_url)
-        .set_chains(&[Chain::Ethereum]) // TODO: handle multichain
+        .set_chains(&chains)
         .build_direct_gw()
         .await?;
 
@@ -336,6 +349,20 @@ async fn run_rpc(global_args: GlobalArgs) -> Result<(), ExtractionError> {
     res.expect("ServiceTasks shouldn't panic!")
 }
 
+/// Parses the chains passed on the command line, at least one chain is required.
+fn parse_chains(chains: &[String]) -> Result<Vec<Chain>, ExtractionError> {
+    if chains.is_empty() {
+        return Err(ExtractionError::Setup("No chain provided".to_string()));
+    }
+    chains
+        .iter()
+        .map(|chain_str| {
+            Chain::from_str(chain_str)
+                .map_err(|_| ExtractionError::Setup(format!("Unknown chain {chain_str}")))
+        })
+        .collect()
+}
+
 /// Creates extraction and server tasks.
 async fn create_indexing_tasks(
     global_args: &GlobalArgs,
@@ -344,14 +371,15 @@ async fn create_indexing_tasks(
     extractors_config: ExtractorConfigs,
     extraction_runtime: Option<&Handle>,
 ) -> Result<(ExtractionTasks, ServerTasks), ExtractionError> {
+    let chain = *chains
+        .first()
+        .ok_or_else(|| ExtractionError::Setup("No chain provided".to_string()))?;
     let rpc_client = EthereumRpcClient::new_from_url(&global_args.rpc_url.clone());
     let block_number = rpc_client
         .get_block_number()
         .await
         .expect("Error getting block number");
 
-    let chain_state = ChainState::new(chrono::Local::now().naive_utc(), block_number, 12); //TODO: remove hardcoded blocktime
-
     let protocol_systems: Vec<String> = extractors_config
         .extractors
         .keys()
@@ -366,14 +394,12 @@ async fn create_indexing_tasks(
         .await?;
     let token_processor = EthereumTokenPreProcessor::new_from_url(
         &global_args.rpc_url.clone(),
-        *chains
-            .first()
-            .expect("No chain provided"), //TODO: handle multichain?
+        chain, //TODO: handle multichain?
     );
 
     let (tasks, extractor_handles): (Vec<_>, Vec<_>) =
         // TODO: accept substreams configuration from cli.
-        build_all_extractors(&extractors_config, chain_state, chains, &global_args.endpoint_url,global_args.s3_bucket.as_deref(), &cached_gw, &token_processor, &global_args.rpc_url.clone(), extraction_runtime)
+        build_all_extractors(&extractors_config, block_number, chains, &global_args.endpoint_url,global_args.s3_bucket.as_deref(), &cached_gw, &token_processor, &global_args.rpc_url.clone(), extraction_runtime)
             .await
             .map_err(|e| ExtractionError::Setup(format!("Failed to create extractors: {e}")))?
             .into_iter()
@@ -401,7 +427,7 @@ async fn create_indexing_tasks(
 #[allow(clippy::too_many_arguments)]
 async fn build_all_extractors(
     config: &ExtractorConfigs,
-    chain_state: ChainState,
+    block_number: u64,
     chains: &[Chain],
     endpoint_url: &str,
     s3_bucket: Option<&str>,
@@ -411,25 +437,39 @@ async fn build_all_extractors(
     runtime: Option<&tokio::runtime::Handle>,
 ) -> Result<Vec<HandleResult>, ExtractionError> {
     let mut extractor_handles = Vec::new();
+    let start = chrono::Local::now().naive_utc();
 
     info!("Building protocol cache");
+    //TODO: handle multichain?
+    let cache_chain = *chains
+        .first()
+        .ok_or_else(|| ExtractionError::Setup("No chain provided".to_string()))?;
     let protocol_cache = ProtocolMemoryCache::new(
-        *chains
-            .first()
-            .expect("No chain provided"), //TODO: handle multichain?
+        cache_chain,
         chrono::Duration::seconds(900),
         Arc::new(cached_gw.clone()),
     );
     protocol_cache.populate().await?;
+    let shared_accounts = SharedAccountStore::new();
 
     for extractor_config in config.extractors.values() {
+        let chain = extractor_config.chain();
+        if !chains.contains(&chain) {
+            return Err(ExtractionError::Setup(format!(
+                "Extractor {} indexes {chain}, which is not configured",
+                extractor_config.name()
+            )));
+        }
+        let chain_state =
+            ChainState::new(start, block_number, chain.spec().block_time.as_secs() as i64);
+
         initialize_accounts(
             extractor_config
                 .initialized_accounts
                 .clone(),
             extractor_config.initialized_accounts_block,
             rpc_url,
-            *chains.first().unwrap(),
+            chain,
             cached_gw,
         )
         .await;
@@ -440,6 +480,7 @@ async fn build_all_extractors(
 
         let (task, handle) = ExtractorBuilder::new(extractor_config, endpoint_url, s3_bucket)
             .rpc_url(rpc_url)
+            .shared_accounts(&shared_accounts)
             .build(chain_state, cached_gw, token_pre_processor, &protocol_cache)
             .await?
             .set_runtime(runtime)
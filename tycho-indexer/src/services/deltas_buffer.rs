@@ -12,7 +12,7 @@ use tycho_common::{
         blockchain::BlockAggregatedChanges,
         contract::Account,
         protocol::{ProtocolComponent, ProtocolComponentState},
-        MergeError,
+        BlockHash, MergeError,
     },
     storage::StorageError,
     Bytes,
@@ -87,6 +87,8 @@ pub trait PendingDeltasBuffer {
         f: &dyn Fn(&BlockAggregatedChanges) -> bool,
         protocol_system: &str,
     ) -> Result<Option<BlockAggregatedChanges>>;
+
+    fn is_orphaned(&self, hash: &BlockHash, protocol_system: &str) -> Result<bool>;
 }
 
 impl PendingDeltas {
@@ -467,6 +469,23 @@ impl PendingDeltasBuffer for PendingDeltas {
 
         Ok(None)
     }
+
+    /// Returns whether the block with the given hash was recently reverted for the given
+    /// protocol system. Can error if the lock is poisoned or the protocol system is unknown.
+    fn is_orphaned(&self, hash: &BlockHash, protocol_system: &str) -> Result<bool> {
+        let buffer = self
+            .buffers
+            .get(protocol_system)
+            .ok_or_else(|| {
+                error!("Missing reorg buffer for {}", protocol_system);
+                PendingDeltasError::UnknownExtractor(protocol_system.to_string())
+            })?;
+        let guard = buffer.lock().map_err(|e| {
+            PendingDeltasError::LockError(protocol_system.to_string(), e.to_string())
+        })?;
+
+        Ok(guard.is_orphaned(hash))
+    }
 }
 
 #[cfg(test)]
@@ -12,10 +12,11 @@ use tokio::task::JoinHandle;
 use tracing::info;
 use tycho_common::{
     dto::{
-        AccountUpdate, BlockParam, Chain, ChangeType, ComponentTvlRequestBody,
-        ComponentTvlRequestResponse, ContractId, Health, PaginationParams, PaginationResponse,
-        ProtocolComponent, ProtocolComponentRequestResponse, ProtocolComponentsRequestBody,
-        ProtocolId, ProtocolStateDelta, ProtocolStateRequestBody, ProtocolStateRequestResponse,
+        AccountUpdate, BlockParam, Chain, ChangeType, ComponentDailyFees, ComponentFeesRequestBody,
+        ComponentFeesRequestResponse, ComponentTvlRequestBody, ComponentTvlRequestResponse,
+        ContractId, Health, PaginationParams, PaginationResponse, ProtocolComponent,
+        ProtocolComponentRequestResponse, ProtocolComponentsRequestBody, ProtocolId,
+        ProtocolStateDelta, ProtocolStateRequestBody, ProtocolStateRequestResponse,
         ProtocolSystemsRequestBody, ProtocolSystemsRequestResponse, ResponseAccount,
         ResponseProtocolState, ResponseToken, StateRequestBody, StateRequestResponse,
         TokensRequestBody, TokensRequestResponse, TracedEntryPointRequestBody,
@@ -114,6 +115,7 @@ where
                 rpc::protocol_state,
                 rpc::contract_state,
                 rpc::component_tvl,
+                rpc::component_fees,
             ),
             components(
                 schemas(VersionParam),
@@ -145,6 +147,9 @@ where
                 schemas(ProtocolSystemsRequestResponse),
                 schemas(ComponentTvlRequestBody),
                 schemas(ComponentTvlRequestResponse),
+                schemas(ComponentFeesRequestBody),
+                schemas(ComponentFeesRequestResponse),
+                schemas(ComponentDailyFees),
             ),
             modifiers(&SecurityAddon),
         )]
@@ -284,6 +289,10 @@ where
                     web::resource(format!("/{}/component_tvl", self.prefix))
                         .route(web::post().to(rpc::component_tvl::<G, EVMEntrypointService>)),
                 )
+                .service(
+                    web::resource(format!("/{}/component_fees", self.prefix))
+                        .route(web::post().to(rpc::component_fees::<G, EVMEntrypointService>)),
+                )
                 .wrap(RequestTracing::new())
                 .service(
                     SwaggerUi::new("/docs/{_:.*}").url("/api-docs/openapi.json", openapi.clone()),
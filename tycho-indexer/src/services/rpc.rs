@@ -82,6 +82,19 @@ impl ResponseError for RpcError {
     }
 }
 
+/// Rejects requests containing addresses that are not well-formed on the requested chain.
+fn validate_addresses(chain: Chain, addresses: Option<&[Address]>) -> Result<(), RpcError> {
+    let spec = chain.spec();
+    match addresses
+        .unwrap_or_default()
+        .iter()
+        .find(|address| !spec.is_valid_address(address))
+    {
+        Some(invalid) => Err(RpcError::Parse(format!("Invalid {chain} address: {invalid}"))),
+        None => Ok(()),
+    }
+}
+
 pub struct RpcHandler<G, T> {
     db_gateway: G,
     // TODO: remove use of Arc. It was introduced for ease of testing this deltas buffer, however
@@ -171,6 +184,7 @@ where
     ) -> Result<dto::StateRequestResponse, RpcError> {
         let at = BlockOrTimestamp::try_from(&request.version)?;
         let chain = request.chain.into();
+        validate_addresses(chain, request.contract_ids.as_deref())?;
         let (db_version, deltas_version) = self
             .calculate_versions(&at, &request.protocol_system.clone(), chain)
             .await?;
@@ -279,6 +293,19 @@ where
                     .and_then(|block| block.map(|b| b.block.number))
                 {
                     Some(block_number)
+                } else if self
+                    .pending_deltas
+                    .as_ref()
+                    .and_then(|pending| {
+                        pending
+                            .is_orphaned(hash, protocol_system)
+                            .ok()
+                    })
+                    .unwrap_or(false)
+                {
+                    // The hash was reverted: resolving it by number would silently return state
+                    // from the new canonical fork.
+                    return Err(RpcError::Storage(StorageError::OrphanedBlock(hash.clone())));
                 } else {
                     self.db_gateway
                         .get_block(&BlockIdentifier::Hash(hash.clone()))
@@ -556,6 +583,58 @@ where
         }
     }
 
+    #[instrument(skip(self, request))]
+    async fn get_component_fees(
+        &self,
+        request: &dto::ComponentFeesRequestBody,
+    ) -> Result<dto::ComponentFeesRequestResponse, RpcError> {
+        info!(?request, "Getting protocol component fees.");
+        if request.start_date > request.end_date {
+            return Err(RpcError::Parse(format!(
+                "start_date {} is after end_date {}",
+                request.start_date, request.end_date
+            )));
+        }
+        let chain = request.chain.into();
+        let pagination_params: PaginationParams = (&request.pagination).into();
+        let ids_strs: Option<Vec<&str>> = request
+            .component_ids
+            .as_ref()
+            .map(|vec| vec.iter().map(String::as_str).collect());
+
+        let ids_slice = ids_strs.as_deref();
+
+        let fees_result = self
+            .db_gateway
+            .get_component_daily_fees(
+                &chain,
+                request.protocol_system.clone(),
+                ids_slice,
+                request.start_date,
+                request.end_date,
+                Some(&pagination_params),
+            )
+            .await;
+
+        match fees_result {
+            Ok(fees) => Ok(dto::ComponentFeesRequestResponse::new(
+                fees.entity
+                    .into_iter()
+                    .map(dto::ComponentDailyFees::from)
+                    .collect(),
+                PaginationResponse::new(
+                    pagination_params.page,
+                    pagination_params.page_size,
+                    fees.total.unwrap_or_default(),
+                ),
+            )),
+            Err(err) => {
+                error!(error = %err, "Error while getting component fees.");
+                Err(err.into())
+            }
+        }
+    }
+
     #[instrument(skip(self, request))]
     async fn get_tokens(
         &self,
@@ -582,6 +661,7 @@ where
         &self,
         request: dto::TokensRequestBody,
     ) -> Result<dto::TokensRequestResponse, RpcError> {
+        validate_addresses(request.chain.into(), request.token_addresses.as_deref())?;
         let address_refs: Option<Vec<&Address>> = request
             .token_addresses
             .as_ref()
@@ -1296,6 +1376,48 @@ pub async fn component_tvl<G: Gateway, T: EntryPointTracer>(
     }
 }
 
+/// Retrieve protocol component fees
+///
+/// This endpoint retrieves the LP and protocol fees accrued by components, aggregated per day
+/// (UTC) and token.
+#[utoipa::path(
+    post,
+    path = "/v1/component_fees",
+    responses(
+        (status = 200, description = "OK", body = ComponentFeesRequestResponse),
+    ),
+    request_body = ComponentFeesRequestBody,
+    security(
+         ("apiKey" = [])
+    ),
+)]
+pub async fn component_fees<G: Gateway, T: EntryPointTracer>(
+    body: web::Json<dto::ComponentFeesRequestBody>,
+    handler: web::Data<RpcHandler<G, T>>,
+) -> HttpResponse {
+    // Tracing and metrics
+    tracing::Span::current().record("page", body.pagination.page);
+    tracing::Span::current().record("page.size", body.pagination.page_size);
+    counter!("rpc_requests", "endpoint" => "component_fees").increment(1);
+
+    // Call the handler to get component fees
+    let response = handler
+        .into_inner()
+        .get_component_fees(&body)
+        .await;
+
+    match response {
+        Ok(fees) => HttpResponse::Ok().json(fees),
+        Err(err) => {
+            error!(error = %err, ?body, "Error while getting component fees.");
+            let status = err.status_code().as_u16().to_string();
+            counter!("rpc_requests_failed", "endpoint" => "component_fees", "status" => status)
+                .increment(1);
+            HttpResponse::from_error(err)
+        }
+    }
+}
+
 /// Retrieve traced entry points
 ///
 /// This endpoint retrieves the traced entry points available in the indexer
@@ -1405,7 +1527,7 @@ mod tests {
     use std::{collections::HashMap, env, str::FromStr};
 
     use actix_web::test;
-    use chrono::NaiveDateTime;
+    use chrono::{NaiveDate, NaiveDateTime};
     use mockall::{mock, predicate::eq};
     use tycho_common::{
         keccak256,
@@ -1415,7 +1537,7 @@ mod tests {
                 TracingResult,
             },
             contract::Account,
-            protocol::{ProtocolComponent, ProtocolComponentState},
+            protocol::{ComponentDailyFees, ProtocolComponent, ProtocolComponentState},
             token::Token,
             ChangeType,
         },
@@ -1468,6 +1590,12 @@ mod tests {
                 f: &dyn Fn(&BlockAggregatedChanges) -> bool,
                 protocol_system: &'a str,
             ) -> Result<Option<BlockAggregatedChanges>,PendingDeltasError>;
+
+            fn is_orphaned<'a>(
+                &self,
+                hash: &'a Bytes,
+                protocol_system: &'a str,
+            ) -> Result<bool, PendingDeltasError>;
         }
     }
 
@@ -2351,6 +2479,24 @@ mod tests {
         println!("curl -X POST -H \"Content-Type: application/json\" -d '{json_data}' {endpoint}");
     }
 
+    #[tokio::test]
+    async fn test_get_tokens_invalid_address() {
+        let req_handler = RpcHandler::new(MockGateway::new(), None, MockEntryPointTracer::new());
+
+        let request = dto::TokensRequestBody {
+            token_addresses: Some(vec![Bytes::from("0x01")]),
+            min_quality: None,
+            traded_n_days_ago: None,
+            pagination: dto::PaginationParams { page: 0, page_size: 2 },
+            chain: dto::Chain::Ethereum,
+        };
+        let res = req_handler
+            .get_tokens_inner(request)
+            .await;
+
+        assert!(matches!(res, Err(RpcError::Parse(_))));
+    }
+
     #[tokio::test]
     async fn test_get_tokens() {
         let expected = vec![
@@ -2454,6 +2600,47 @@ mod tests {
         assert_eq!(res.pagination.total, 2);
     }
 
+    #[tokio::test]
+    async fn test_get_protocol_state_at_orphaned_block_hash() {
+        let gw = MockGateway::new();
+        let orphaned_hash = Bytes::from(7u8).lpad(32, 0);
+
+        let mut mock_buffer = MockPendingDeltas::new();
+        mock_buffer
+            .expect_search_block()
+            .return_once(|_, _| Ok(None));
+        mock_buffer
+            .expect_is_orphaned()
+            .return_once(|_, _| Ok(true));
+
+        let req_handler =
+            RpcHandler::new(gw, Some(Arc::new(mock_buffer)), MockEntryPointTracer::new());
+
+        let request = dto::ProtocolStateRequestBody {
+            protocol_ids: Some(vec!["state1".to_owned()]),
+            protocol_system: "uniswap_v2".to_string(),
+            chain: dto::Chain::Ethereum,
+            include_balances: true,
+            version: dto::VersionParam {
+                timestamp: None,
+                block: Some(dto::BlockParam {
+                    hash: Some(orphaned_hash.clone()),
+                    chain: None,
+                    number: None,
+                }),
+            },
+            pagination: dto::PaginationParams::default(),
+        };
+        let res = req_handler
+            .get_protocol_state_inner(request)
+            .await;
+
+        assert!(matches!(
+            res,
+            Err(RpcError::Storage(StorageError::OrphanedBlock(hash))) if hash == orphaned_hash
+        ));
+    }
+
     fn protocol_attributes<'a>(
         data: impl IntoIterator<Item = (&'a str, i32)>,
     ) -> HashMap<String, Bytes> {
@@ -2646,4 +2833,55 @@ mod tests {
         assert_eq!(response2.protocol_components[0], buf_expected2.into());
         assert_eq!(response2.pagination.total, 3);
     }
+
+    #[tokio::test]
+    async fn test_get_component_fees() {
+        let mut gw = MockGateway::new();
+        let day = NaiveDate::from_ymd_opt(2025, 8, 4).unwrap();
+        let daily_fees = ComponentDailyFees {
+            component_id: "comp1".to_string(),
+            token: Bytes::from_str(WETH).unwrap(),
+            day,
+            lp_fees: Bytes::from(300u64).lpad(32, 0),
+            lp_fees_float: 300.0,
+            protocol_fees: Bytes::from(30u64).lpad(32, 0),
+            protocol_fees_float: 30.0,
+        };
+        let mock_response = Ok(WithTotal { entity: vec![daily_fees.clone()], total: Some(1) });
+        gw.expect_get_component_daily_fees()
+            .return_once(|_, _, _, _, _, _| Box::pin(async move { mock_response }));
+        let req_handler = RpcHandler::new(gw, None, MockEntryPointTracer::new());
+        let request = dto::ComponentFeesRequestBody::id_filtered(
+            vec!["comp1".to_string()],
+            day,
+            day,
+            dto::Chain::Ethereum,
+        );
+
+        let response = req_handler
+            .get_component_fees(&request)
+            .await
+            .unwrap();
+
+        assert_eq!(response.fees.len(), 1);
+        assert_eq!(response.fees[0], daily_fees.into());
+        assert_eq!(response.pagination.total, 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_component_fees_invalid_range() {
+        let req_handler = RpcHandler::new(MockGateway::new(), None, MockEntryPointTracer::new());
+        let request = dto::ComponentFeesRequestBody::id_filtered(
+            vec!["comp1".to_string()],
+            NaiveDate::from_ymd_opt(2025, 8, 4).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 8, 3).unwrap(),
+            dto::Chain::Ethereum,
+        );
+
+        let res = req_handler
+            .get_component_fees(&request)
+            .await;
+
+        assert!(matches!(res, Err(RpcError::Parse(_))));
+    }
 }
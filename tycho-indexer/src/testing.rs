@@ -4,7 +4,7 @@ use std::{
 };
 
 use async_trait::async_trait;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use mockall::mock;
 use tycho_common::{
     models::{
@@ -14,8 +14,8 @@ use tycho_common::{
         },
         contract::{Account, AccountBalance, AccountDelta},
         protocol::{
-            ComponentBalance, ProtocolComponent, ProtocolComponentState,
-            ProtocolComponentStateDelta, QualityRange,
+            ComponentBalance, ComponentDailyFees, ComponentFeeAccrual, ProtocolComponent,
+            ProtocolComponentState, ProtocolComponentStateDelta, QualityRange,
         },
         token::Token,
         Address, Chain, ComponentId, ContractId, EntryPointId, ExtractionState, PaginationParams,
@@ -35,6 +35,7 @@ mock! {
     impl ExtractionStateGateway for Gateway {
         async fn get_state(&self, name: &str, chain: &Chain) -> Result<ExtractionState, StorageError>;
         async fn save_state(&self, state: &ExtractionState) -> Result<(), StorageError>;
+        async fn revert_extraction_state(&self, state: &ExtractionState) -> Result<(), StorageError>;
     }
 
     #[async_trait]
@@ -479,6 +480,21 @@ mock! {
             'life1: 'async_trait,
             Self: 'async_trait;
 
+        fn add_component_fee_accruals<'life0, 'life1, 'async_trait>(
+            &'life0 self,
+            accruals: &'life1 [ComponentFeeAccrual],
+        ) -> ::core::pin::Pin<
+            Box<
+                dyn ::core::future::Future<
+                    Output = Result<(), StorageError>,
+                > + ::core::marker::Send + 'async_trait,
+            >,
+        >
+        where
+            'life0: 'async_trait,
+            'life1: 'async_trait,
+            Self: 'async_trait;
+
         fn add_tokens<'life0, 'life1, 'async_trait>(
             &'life0 self,
             tokens: &'life1 [Token],
@@ -646,6 +662,30 @@ mock! {
             'life3: 'async_trait,
             'life4: 'async_trait,
             Self: 'async_trait;
+
+        #[allow(clippy::too_many_arguments)]
+        fn get_component_daily_fees<'life0, 'life1, 'life2, 'life3, 'life4, 'async_trait>(
+            &'life0 self,
+            chain: &'life1 Chain,
+            system: Option<String>,
+            ids: Option<&'life2 [&'life3 str]>,
+            start: NaiveDate,
+            end: NaiveDate,
+            pagination_params: Option<&'life4 PaginationParams>,
+        ) -> ::core::pin::Pin<
+            Box<
+                dyn ::core::future::Future<
+                    Output = Result<WithTotal<Vec<ComponentDailyFees>>, StorageError>,
+                > + ::core::marker::Send + 'async_trait,
+            >,
+        >
+        where
+            'life0: 'async_trait,
+            'life1: 'async_trait,
+            'life2: 'async_trait,
+            'life3: 'async_trait,
+            'life4: 'async_trait,
+            Self: 'async_trait;
     }
 
     impl Gateway for Gateway {}
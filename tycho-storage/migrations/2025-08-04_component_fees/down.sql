@@ -0,0 +1,4 @@
+DROP TRIGGER IF EXISTS rollup_component_fee_accrual ON component_fee_accrual;
+DROP FUNCTION IF EXISTS rollup_component_fee_accrual();
+DROP TABLE IF EXISTS "component_fee_daily";
+DROP TABLE IF EXISTS "component_fee_accrual";
@@ -0,0 +1,93 @@
+-- Fees accrued by a component within a single block, amounts are given in the token's smallest unit.
+CREATE TABLE IF NOT EXISTS "component_fee_accrual"(
+    "id" bigserial PRIMARY KEY,
+    "protocol_component_id" bigint REFERENCES "protocol_component"(id) ON DELETE CASCADE NOT NULL,
+    "token_id" bigint REFERENCES "token"(id) ON DELETE CASCADE NOT NULL,
+    "block_id" bigint REFERENCES "block"(id) ON DELETE CASCADE NOT NULL,
+    -- Denormalised block timestamp, required to maintain the daily rollups on deletes.
+    "ts" timestamptz NOT NULL,
+    "lp_fees" numeric(78, 0) NOT NULL,
+    "protocol_fees" numeric(78, 0) NOT NULL,
+    "inserted_ts" timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE ("protocol_component_id", "token_id", "block_id")
+);
+
+CREATE INDEX IF NOT EXISTS idx_component_fee_accrual_block_id ON component_fee_accrual (block_id);
+
+-- Required to find the remaining accruals of a day once an accrual is deleted.
+CREATE INDEX IF NOT EXISTS idx_component_fee_accrual_component_token_ts ON component_fee_accrual
+    (protocol_component_id, token_id, ts);
+
+-- Fees accrued by a component within a single day (UTC). Maintained by triggers on
+-- component_fee_accrual, so reverted blocks are removed from the rollups as well.
+CREATE TABLE IF NOT EXISTS "component_fee_daily"(
+    "protocol_component_id" bigint REFERENCES "protocol_component"(id) ON DELETE CASCADE NOT NULL,
+    "token_id" bigint REFERENCES "token"(id) ON DELETE CASCADE NOT NULL,
+    "day" date NOT NULL,
+    "lp_fees" numeric(78, 0) NOT NULL,
+    "protocol_fees" numeric(78, 0) NOT NULL,
+    "inserted_ts" timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "modified_ts" timestamptz NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY ("protocol_component_id", "token_id", "day")
+);
+
+CREATE INDEX IF NOT EXISTS idx_component_fee_daily_day ON component_fee_daily (day);
+
+CREATE TRIGGER update_modtime_component_fee_daily
+    BEFORE UPDATE ON "component_fee_daily"
+    FOR EACH ROW
+    EXECUTE PROCEDURE update_modified_column();
+
+CREATE OR REPLACE FUNCTION rollup_component_fee_accrual()
+    RETURNS TRIGGER
+    AS $$
+DECLARE
+    day_start timestamptz;
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO component_fee_daily (protocol_component_id, token_id, day, lp_fees, protocol_fees)
+        VALUES (NEW.protocol_component_id, NEW.token_id, (NEW.ts AT TIME ZONE 'UTC')::date, NEW.lp_fees, NEW.protocol_fees)
+        ON CONFLICT (protocol_component_id, token_id, day)
+        DO UPDATE SET
+            lp_fees = component_fee_daily.lp_fees + EXCLUDED.lp_fees,
+            protocol_fees = component_fee_daily.protocol_fees + EXCLUDED.protocol_fees;
+        RETURN NEW;
+    END IF;
+    -- Rollups of deleted components or tokens are removed by the cascade and must not be touched.
+    IF NOT EXISTS (SELECT 1 FROM protocol_component WHERE id = OLD.protocol_component_id)
+        OR NOT EXISTS (SELECT 1 FROM token WHERE id = OLD.token_id) THEN
+        RETURN OLD;
+    END IF;
+    -- Remove deleted accruals, e.g. of reverted blocks, from the rollup. A day without remaining
+    -- accruals is removed entirely, so a revert restores the rollups exactly.
+    day_start := date_trunc('day', OLD.ts AT TIME ZONE 'UTC') AT TIME ZONE 'UTC';
+    IF EXISTS (
+        SELECT 1 FROM component_fee_accrual
+        WHERE protocol_component_id = OLD.protocol_component_id
+            AND token_id = OLD.token_id
+            AND ts >= day_start
+            AND ts < day_start + interval '1 day') THEN
+        UPDATE
+            component_fee_daily
+        SET
+            lp_fees = lp_fees - OLD.lp_fees,
+            protocol_fees = protocol_fees - OLD.protocol_fees
+        WHERE
+            protocol_component_id = OLD.protocol_component_id
+            AND token_id = OLD.token_id
+            AND day = (OLD.ts AT TIME ZONE 'UTC')::date;
+    ELSE
+        DELETE FROM component_fee_daily
+        WHERE protocol_component_id = OLD.protocol_component_id
+            AND token_id = OLD.token_id
+            AND day = (OLD.ts AT TIME ZONE 'UTC')::date;
+    END IF;
+    RETURN OLD;
+END;
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER rollup_component_fee_accrual
+    AFTER INSERT OR DELETE ON component_fee_accrual
+    FOR EACH ROW
+    EXECUTE PROCEDURE rollup_component_fee_accrual();
@@ -2,10 +2,11 @@ use std::{
     collections::{HashMap, HashSet},
     num::NonZeroUsize,
     sync::Arc,
+    time::Instant,
 };
 
 use async_trait::async_trait;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use diesel_async::{
     pooled_connection::deadpool::Pool, scoped_futures::ScopedFutureExt, AsyncConnection,
     AsyncPgConnection,
@@ -25,8 +26,8 @@ use tycho_common::{
         },
         contract::{Account, AccountBalance, AccountDelta},
         protocol::{
-            ComponentBalance, ProtocolComponent, ProtocolComponentState,
-            ProtocolComponentStateDelta, QualityRange,
+            ComponentBalance, ComponentDailyFees, ComponentFeeAccrual, ProtocolComponent,
+            ProtocolComponentState, ProtocolComponentStateDelta, QualityRange,
         },
         token::Token,
         Address, Chain, ComponentId, ContractId, EntryPointId, ExtractionState, PaginationParams,
@@ -40,7 +41,10 @@ use tycho_common::{
     Bytes,
 };
 
-use super::{PostgresError, PostgresGateway};
+use super::{
+    trace::{table_rows_diff, transaction_table_rows, WriteOpTrace, WriteTrace},
+    PostgresError, PostgresGateway,
+};
 
 /// Represents different types of database write operations.
 #[derive(PartialEq, Clone, Debug)]
@@ -67,6 +71,8 @@ pub(crate) enum WriteOp {
     // Simply merge
     InsertComponentBalances(Vec<models::protocol::ComponentBalance>),
     // Simply merge
+    InsertComponentFeeAccruals(Vec<models::protocol::ComponentFeeAccrual>),
+    // Simply merge
     UpsertProtocolState(Vec<(TxHash, models::protocol::ProtocolComponentStateDelta)>),
     // Simply merge
     InsertEntryPoints(HashMap<models::ComponentId, HashSet<models::blockchain::EntryPoint>>),
@@ -91,6 +97,7 @@ impl WriteOp {
             WriteOp::InsertTokens(_) => "InsertTokens",
             WriteOp::UpdateTokens(_) => "UpdateTokens",
             WriteOp::InsertComponentBalances(_) => "InsertComponentBalances",
+            WriteOp::InsertComponentFeeAccruals(_) => "InsertComponentFeeAccruals",
             WriteOp::UpsertProtocolState(_) => "UpsertProtocolState",
             WriteOp::InsertEntryPoints(_) => "InsertEntryPoints",
             WriteOp::InsertEntryPointTracingParams(_) => "InsertEntryPointTracingParams",
@@ -98,6 +105,27 @@ impl WriteOp {
         }
     }
 
+    /// Number of entities written by this operation.
+    fn n_entities(&self) -> usize {
+        match self {
+            WriteOp::UpsertBlock(v) => v.len(),
+            WriteOp::UpsertTx(v) => v.len(),
+            WriteOp::SaveExtractionState(_) => 1,
+            WriteOp::InsertContract(v) => v.len(),
+            WriteOp::UpdateContracts(v) => v.len(),
+            WriteOp::InsertAccountBalances(v) => v.len(),
+            WriteOp::InsertProtocolComponents(v) => v.len(),
+            WriteOp::InsertTokens(v) => v.len(),
+            WriteOp::UpdateTokens(v) => v.len(),
+            WriteOp::InsertComponentBalances(v) => v.len(),
+            WriteOp::InsertComponentFeeAccruals(v) => v.len(),
+            WriteOp::UpsertProtocolState(v) => v.len(),
+            WriteOp::InsertEntryPoints(m) => m.values().map(HashSet::len).sum(),
+            WriteOp::InsertEntryPointTracingParams(m) => m.values().map(HashSet::len).sum(),
+            WriteOp::UpsertTracedEntryPoints(v) => v.len(),
+        }
+    }
+
     fn order_key(&self) -> usize {
         match self {
             WriteOp::UpsertBlock(_) => 0,
@@ -109,11 +137,12 @@ impl WriteOp {
             WriteOp::InsertAccountBalances(_) => 6,
             WriteOp::InsertProtocolComponents(_) => 7,
             WriteOp::InsertComponentBalances(_) => 8,
-            WriteOp::UpsertProtocolState(_) => 9,
-            WriteOp::InsertEntryPoints(_) => 10,
-            WriteOp::InsertEntryPointTracingParams(_) => 11,
-            WriteOp::UpsertTracedEntryPoints(_) => 12,
-            WriteOp::SaveExtractionState(_) => 13,
+            WriteOp::InsertComponentFeeAccruals(_) => 9,
+            WriteOp::UpsertProtocolState(_) => 10,
+            WriteOp::InsertEntryPoints(_) => 11,
+            WriteOp::InsertEntryPointTracingParams(_) => 12,
+            WriteOp::UpsertTracedEntryPoints(_) => 13,
+            WriteOp::SaveExtractionState(_) => 14,
         }
     }
 }
@@ -149,6 +178,8 @@ pub struct DBTransaction {
     tx: oneshot::Sender<Result<(), StorageError>>,
     /// Purely used to add an attribute to the span when the transaction is commited
     owner: Option<String>,
+    /// If set, the executed operations are traced and the trace is sent through this channel.
+    trace_tx: Option<oneshot::Sender<WriteTrace>>,
 }
 
 impl DBTransaction {
@@ -210,6 +241,14 @@ impl DBTransaction {
                     l.extend(r.iter().cloned());
                     return Ok(());
                 }
+                (
+                    WriteOp::InsertComponentFeeAccruals(l),
+                    WriteOp::InsertComponentFeeAccruals(r),
+                ) => {
+                    self.size += r.len();
+                    l.extend(r.iter().cloned());
+                    return Ok(());
+                }
                 (WriteOp::UpsertProtocolState(l), WriteOp::UpsertProtocolState(r)) => {
                     self.size += r.len();
                     l.extend(r.iter().cloned());
@@ -350,7 +389,7 @@ impl DBCacheWriteExecutor {
     }
 
     #[instrument(name="db_write", skip_all, fields(block_range = %new_db_tx.block_range, extractor_id = tracing::field::Empty))]
-    async fn write(&mut self, new_db_tx: DBTransaction) {
+    async fn write(&mut self, mut new_db_tx: DBTransaction) {
         debug!("NewDBTransactionStart");
         if let Some(extractor_id) = new_db_tx.owner.as_ref() {
             tracing::Span::current().record("extractor_id", extractor_id);
@@ -362,19 +401,54 @@ impl DBCacheWriteExecutor {
             .await
             .expect("pool should be connected");
 
+        let trace_tx = new_db_tx.trace_tx.take();
+        let mut op_traces = trace_tx.as_ref().map(|_| Vec::new());
+        let started = Instant::now();
+        let mut attempts = 0;
+
         let mut retry_count = 0;
         let max_retries = 3;
         let mut res =
             Err(PostgresError(StorageError::Unexpected("default response error".to_string())));
 
         while retry_count < max_retries {
+            attempts += 1;
+            if let Some(traces) = op_traces.as_mut() {
+                traces.clear();
+            }
             res = conn
                 .build_transaction()
                 .repeatable_read()
                 .run(|conn| {
                     async {
                         for op in new_db_tx.operations.iter() {
-                            match self.execute_write_op(op, conn).await {
+                            let rows_before = match op_traces {
+                                Some(_) => transaction_table_rows(conn).await.ok(),
+                                None => None,
+                            };
+                            let op_started = Instant::now();
+                            let op_res = self.execute_write_op(op, conn).await;
+                            let duration = op_started.elapsed();
+                            if let Some(traces) = op_traces.as_mut() {
+                                let tables = match (&rows_before, op_res.is_ok()) {
+                                    (Some(before), true) => transaction_table_rows(conn)
+                                        .await
+                                        .ok()
+                                        .map(|after| table_rows_diff(before, after)),
+                                    _ => None,
+                                };
+                                traces.push(WriteOpTrace {
+                                    operation: op.variant_name().to_string(),
+                                    entities: op.n_entities(),
+                                    tables,
+                                    duration,
+                                    error: op_res
+                                        .as_ref()
+                                        .err()
+                                        .map(|e| e.0.to_string()),
+                                });
+                            }
+                            match op_res {
                                 Err(PostgresError(StorageError::DuplicateEntry(entity, id))) => {
                                     // As this db transaction is old. It can contain
                                     // already stored txs, we log the duplicate entry
@@ -422,6 +496,22 @@ impl DBCacheWriteExecutor {
             debug!("DBTransactionCommitted");
         }
 
+        if let Some(trace_tx) = trace_tx {
+            let trace = WriteTrace {
+                owner: new_db_tx.owner.clone(),
+                start_block: new_db_tx.block_range.start.clone(),
+                end_block: new_db_tx.block_range.end.clone(),
+                attempts,
+                operations: op_traces.unwrap_or_default(),
+                duration: started.elapsed(),
+                error: res
+                    .as_ref()
+                    .err()
+                    .map(|e| e.0.to_string()),
+            };
+            let _ = trace_tx.send(trace);
+        }
+
         match self.persisted_block.as_ref() {
             None => {
                 self.persisted_block = Some(new_db_tx.block_range.end);
@@ -507,6 +597,11 @@ impl DBCacheWriteExecutor {
                     .add_component_balances(balances.as_slice(), &self.chain, conn)
                     .await?
             }
+            WriteOp::InsertComponentFeeAccruals(accruals) => {
+                self.state_gateway
+                    .add_component_fee_accruals(accruals.as_slice(), &self.chain, conn)
+                    .await?
+            }
             WriteOp::UpsertProtocolState(deltas) => {
                 let collected_changes: Vec<(
                     TxHash,
@@ -602,12 +697,53 @@ impl CachedGateway {
                     operations: vec![],
                     tx,
                     owner: owner.map(String::from),
+                    trace_tx: None,
                 },
                 rx,
             ));
         }
     }
 
+    /// Starts a transaction whose writes are traced.
+    ///
+    /// A currently open transaction is committed first, so the trace only covers writes
+    /// made after this call. The returned receiver resolves once the transaction was written to
+    /// the database. Callers usually want to commit the traced transaction right away, otherwise
+    /// it may accumulate the writes of subsequent blocks.
+    pub async fn start_traced_transaction(
+        &self,
+        block: &models::blockchain::Block,
+        owner: Option<&str>,
+    ) -> Result<oneshot::Receiver<WriteTrace>, StorageError> {
+        let mut open_tx = self.open_tx.lock().await;
+        if let Some((db_txn, rx)) = open_tx.take() {
+            self.submit_transaction(db_txn, rx)
+                .await?;
+        }
+
+        let (trace_tx, trace_rx) = oneshot::channel();
+        let (tx, rx) = oneshot::channel();
+        *open_tx = Some((
+            DBTransaction {
+                block_range: BlockRange::new(block, block),
+                size: 0,
+                operations: vec![],
+                tx,
+                owner: owner.map(String::from),
+                trace_tx: Some(trace_tx),
+            },
+            rx,
+        ));
+        Ok(trace_rx)
+    }
+
+    /// Drops the open transaction without writing it, e.g. after staging a block failed.
+    pub async fn discard_transaction(&self) {
+        if let Some((db_txn, _)) = self.open_tx.lock().await.take() {
+            debug!(size = db_txn.size, "Discarded open db transaction");
+        }
+    }
+
     async fn add_op(&self, op: WriteOp) -> Result<(), StorageError> {
         let mut open_tx = self.open_tx.lock().await;
         match open_tx.as_mut() {
@@ -621,39 +757,21 @@ impl CachedGateway {
         }
     }
 
+    /// Returns whether a transaction was started but not submitted to the database yet.
+    pub async fn has_open_transaction(&self) -> bool {
+        self.open_tx.lock().await.is_some()
+    }
+
     pub async fn commit_transaction(&self, min_ops_batch_size: usize) -> Result<(), StorageError> {
         let mut open_tx = self.open_tx.lock().await;
         match open_tx.take() {
             None => {
                 Err(StorageError::Unexpected("Usage error: Commit without transaction".to_string()))
             }
-            Some((mut db_txn, rx)) => {
+            Some((db_txn, rx)) => {
                 if db_txn.size > min_ops_batch_size {
-                    let span = info_span!("DatabaseCommit", size = db_txn.size);
-                    async move {
-                        db_txn
-                            .operations
-                            .sort_by_key(|e| e.order_key());
-                        debug!(
-                            size = db_txn.size,
-                            ops = ?db_txn
-                                .operations
-                                .iter()
-                                .map(WriteOp::variant_name)
-                                .collect::<Vec<_>>(),
-                            "Submitting db operation batch!"
-                        );
-                        self.tx
-                            .send(DBCacheMessage::Write(db_txn))
-                            .await
-                            .expect("Send message to receiver ok");
-                        rx.await
-                            .map_err(|_| StorageError::WriteCacheGoneAway())??;
-
-                        Ok::<(), StorageError>(())
-                    }
-                    .instrument(span)
-                    .await?;
+                    self.submit_transaction(db_txn, rx)
+                        .await?;
                 } else {
                     // if we are not ready to commit, give the OpenTx struct back.
                     *open_tx = Some((db_txn, rx));
@@ -663,6 +781,38 @@ impl CachedGateway {
         }
     }
 
+    async fn submit_transaction(
+        &self,
+        mut db_txn: DBTransaction,
+        rx: oneshot::Receiver<Result<(), StorageError>>,
+    ) -> Result<(), StorageError> {
+        let span = info_span!("DatabaseCommit", size = db_txn.size);
+        async move {
+            db_txn
+                .operations
+                .sort_by_key(|e| e.order_key());
+            debug!(
+                size = db_txn.size,
+                ops = ?db_txn
+                    .operations
+                    .iter()
+                    .map(WriteOp::variant_name)
+                    .collect::<Vec<_>>(),
+                "Submitting db operation batch!"
+            );
+            self.tx
+                .send(DBCacheMessage::Write(db_txn))
+                .await
+                .expect("Send message to receiver ok");
+            rx.await
+                .map_err(|_| StorageError::WriteCacheGoneAway())??;
+
+            Ok::<(), StorageError>(())
+        }
+        .instrument(span)
+        .await
+    }
+
     #[allow(private_interfaces)]
     pub fn new(
         tx: mpsc::Sender<DBCacheMessage>,
@@ -752,6 +902,17 @@ impl ExtractionStateGateway for CachedGateway {
             .await?;
         Ok(())
     }
+
+    #[instrument(skip_all)]
+    async fn revert_extraction_state(&self, state: &ExtractionState) -> Result<(), StorageError> {
+        let mut conn =
+            self.pool.get().await.map_err(|e| {
+                StorageError::Unexpected(format!("Failed to retrieve connection: {e}"))
+            })?;
+        self.state_gateway
+            .revert_extraction_state(state, &mut conn)
+            .await
+    }
 }
 
 #[async_trait]
@@ -797,9 +958,17 @@ impl ChainGateway for CachedGateway {
             self.pool.get().await.map_err(|e| {
                 StorageError::Unexpected(format!("Failed to retrieve connection: {e}"))
             })?;
-        self.state_gateway
-            .revert_state(to, &mut conn)
-            .await
+        conn.transaction(|conn| {
+            async {
+                self.state_gateway
+                    .revert_state(to, conn)
+                    .await?;
+                Result::<(), PostgresError>::Ok(())
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(StorageError::from)
     }
 }
 
@@ -1044,6 +1213,16 @@ impl ProtocolGateway for CachedGateway {
         Ok(())
     }
 
+    #[instrument(skip_all)]
+    async fn add_component_fee_accruals(
+        &self,
+        accruals: &[ComponentFeeAccrual],
+    ) -> Result<(), StorageError> {
+        self.add_op(WriteOp::InsertComponentFeeAccruals(accruals.to_vec()))
+            .await?;
+        Ok(())
+    }
+
     #[instrument(skip_all)]
     async fn add_tokens(&self, tokens: &[Token]) -> Result<(), StorageError> {
         self.add_op(WriteOp::InsertTokens(tokens.to_vec()))
@@ -1182,6 +1361,25 @@ impl ProtocolGateway for CachedGateway {
             .get_component_tvls(chain, system, ids, pagination_params, &mut conn)
             .await
     }
+
+    #[instrument(skip_all)]
+    async fn get_component_daily_fees(
+        &self,
+        chain: &Chain,
+        system: Option<String>,
+        ids: Option<&[&str]>,
+        start: NaiveDate,
+        end: NaiveDate,
+        pagination_params: Option<&PaginationParams>,
+    ) -> Result<WithTotal<Vec<ComponentDailyFees>>, StorageError> {
+        let mut conn =
+            self.pool.get().await.map_err(|e| {
+                StorageError::Unexpected(format!("Failed to retrieve connection: {e}"))
+            })?;
+        self.state_gateway
+            .get_component_daily_fees(chain, system, ids, start, end, pagination_params, &mut conn)
+            .await
+    }
 }
 
 #[async_trait]
@@ -1266,12 +1464,19 @@ impl Gateway for CachedGateway {}
 
 #[cfg(test)]
 mod test_serial_db {
-    use std::{collections::HashSet, slice, str::FromStr, time::Duration};
+    use std::{
+        collections::{BTreeMap, HashSet},
+        slice,
+        str::FromStr,
+        time::Duration,
+    };
 
     use tycho_common::models::ChangeType;
 
     use super::*;
-    use crate::postgres::{db_fixtures, db_fixtures::yesterday_one_am, testing::run_against_db};
+    use crate::postgres::{
+        db_fixtures, db_fixtures::yesterday_one_am, testing::run_against_db, trace::TableRows,
+    };
 
     #[tokio::test]
     async fn test_write_and_flush() {
@@ -1611,6 +1816,165 @@ mod test_serial_db {
         .await;
     }
 
+    #[tokio::test]
+    async fn test_traced_transaction() {
+        run_against_db(|connection_pool| async move {
+            let mut connection = connection_pool
+                .get()
+                .await
+                .expect("Failed to get a connection from the pool");
+            let chain_id = db_fixtures::insert_chain(&mut connection, "ethereum").await;
+            db_fixtures::insert_token(
+                &mut connection,
+                chain_id,
+                "0000000000000000000000000000000000000000",
+                "ETH",
+                18,
+                Some(100),
+            )
+            .await;
+            let gateway: PostgresGateway = PostgresGateway::from_connection(&mut connection).await;
+            let (tx, rx) = mpsc::channel(10);
+
+            let write_executor = DBCacheWriteExecutor::new(
+                "ethereum".to_owned(),
+                Chain::Ethereum,
+                connection_pool.clone(),
+                gateway.clone(),
+                rx,
+            )
+            .await;
+
+            let handle = write_executor.run();
+            let cached_gw = CachedGateway::new(tx, connection_pool.clone(), gateway);
+
+            // Block 1 stays pending as the batch size is not reached
+            let block_1 = get_sample_block(1);
+            cached_gw
+                .start_transaction(&block_1, Some("vm:test"))
+                .await;
+            cached_gw
+                .upsert_block(slice::from_ref(&block_1))
+                .await
+                .expect("Upsert block 1 ok");
+            cached_gw
+                .commit_transaction(10)
+                .await
+                .expect("committing tx failed");
+
+            // Tracing block 2 flushes block 1 first
+            let block_2 = get_sample_block(2);
+            let tx_1 = get_sample_transaction(1);
+            let trace_rx = cached_gw
+                .start_traced_transaction(&block_2, Some("vm:test"))
+                .await
+                .expect("Start traced transaction ok");
+            cached_gw
+                .upsert_block(slice::from_ref(&block_2))
+                .await
+                .expect("Upsert block 2 ok");
+            cached_gw
+                .upsert_tx(slice::from_ref(&tx_1))
+                .await
+                .expect("Upsert tx 1 ok");
+            cached_gw
+                .commit_transaction(0)
+                .await
+                .expect("committing tx failed");
+
+            let trace = trace_rx.await.expect("Trace received");
+            handle.abort();
+
+            assert_eq!(trace.owner, Some("vm:test".to_string()));
+            assert_eq!(trace.start_block, block_2);
+            assert_eq!(trace.end_block, block_2);
+            assert_eq!(trace.attempts, 1);
+            assert_eq!(trace.error, None);
+            assert_eq!(
+                trace
+                    .operations
+                    .iter()
+                    .map(|op| op.operation.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["UpsertBlock", "UpsertTx"]
+            );
+            assert_eq!(
+                trace
+                    .operations
+                    .iter()
+                    .map(|op| op
+                        .tables
+                        .clone()
+                        .expect("Row counts read"))
+                    .collect::<Vec<_>>(),
+                vec![
+                    BTreeMap::from([(
+                        "block".to_string(),
+                        TableRows { inserted: 1, updated: 0, deleted: 0 }
+                    )]),
+                    BTreeMap::from([(
+                        "transaction".to_string(),
+                        TableRows { inserted: 1, updated: 0, deleted: 0 }
+                    )]),
+                ]
+            );
+            assert_eq!(trace.rows(), 2);
+            cached_gw
+                .get_block(&BlockIdentifier::Number((Chain::Ethereum, 1)))
+                .await
+                .expect("Block 1 was flushed");
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_discard_traced_transaction() {
+        run_against_db(|connection_pool| async move {
+            let mut connection = connection_pool
+                .get()
+                .await
+                .expect("Failed to get a connection from the pool");
+            let chain_id = db_fixtures::insert_chain(&mut connection, "ethereum").await;
+            db_fixtures::insert_token(
+                &mut connection,
+                chain_id,
+                "0000000000000000000000000000000000000000",
+                "ETH",
+                18,
+                Some(100),
+            )
+            .await;
+            let gateway: PostgresGateway = PostgresGateway::from_connection(&mut connection).await;
+            let (tx, _rx) = mpsc::channel(10);
+            let cached_gw = CachedGateway::new(tx, connection_pool.clone(), gateway);
+
+            let block_1 = get_sample_block(1);
+            let trace_rx = cached_gw
+                .start_traced_transaction(&block_1, Some("vm:test"))
+                .await
+                .expect("Start traced transaction ok");
+            cached_gw
+                .upsert_block(slice::from_ref(&block_1))
+                .await
+                .expect("Upsert block 1 ok");
+            cached_gw.discard_transaction().await;
+
+            assert!(!cached_gw.has_open_transaction().await);
+            assert!(trace_rx.await.is_err());
+            // the next block starts a new, untraced transaction
+            cached_gw
+                .start_transaction(&get_sample_block(2), Some("vm:test"))
+                .await;
+            assert!(cached_gw
+                .open_tx
+                .lock()
+                .await
+                .as_ref()
+                .is_some_and(|(db_txn, _)| db_txn.trace_tx.is_none() && db_txn.size == 0));
+        })
+        .await;
+    }
+
     fn get_sample_block(version: usize) -> models::blockchain::Block {
         let ts1 = yesterday_one_am();
         let ts2 = ts1 + Duration::from_secs(3600);
@@ -1694,6 +2058,7 @@ mod test_serial_db {
             operations,
             tx: os_tx,
             owner: None,
+            trace_tx: None,
         };
 
         tx.send(DBCacheMessage::Write(db_transaction))
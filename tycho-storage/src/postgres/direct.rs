@@ -1,7 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
 use async_trait::async_trait;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use diesel_async::{
     pooled_connection::deadpool::Pool, scoped_futures::ScopedFutureExt, AsyncConnection,
     AsyncPgConnection,
@@ -16,8 +16,8 @@ use tycho_common::{
         },
         contract::{Account, AccountBalance, AccountDelta},
         protocol::{
-            ComponentBalance, ProtocolComponent, ProtocolComponentState,
-            ProtocolComponentStateDelta, QualityRange,
+            ComponentBalance, ComponentDailyFees, ComponentFeeAccrual, ProtocolComponent,
+            ProtocolComponentState, ProtocolComponentStateDelta, QualityRange,
         },
         token::Token,
         Address, Chain, ComponentId, ContractId, EntryPointId, ExtractionState, PaginationParams,
@@ -109,6 +109,17 @@ impl ExtractionStateGateway for DirectGateway {
             .await?;
         Ok(())
     }
+
+    #[instrument(skip_all)]
+    async fn revert_extraction_state(&self, state: &ExtractionState) -> Result<(), StorageError> {
+        let mut conn =
+            self.pool.get().await.map_err(|e| {
+                StorageError::Unexpected(format!("Failed to retrieve connection: {e}"))
+            })?;
+        self.state_gateway
+            .revert_extraction_state(state, &mut conn)
+            .await
+    }
 }
 
 #[async_trait]
@@ -164,9 +175,17 @@ impl ChainGateway for DirectGateway {
             self.pool.get().await.map_err(|e| {
                 StorageError::Unexpected(format!("Failed to retrieve connection: {e}"))
             })?;
-        self.state_gateway
-            .revert_state(to, &mut conn)
-            .await
+        conn.transaction(|conn| {
+            async {
+                self.state_gateway
+                    .revert_state(to, conn)
+                    .await?;
+                Result::<(), PostgresError>::Ok(())
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(StorageError::from)
     }
 }
 
@@ -454,6 +473,20 @@ impl ProtocolGateway for DirectGateway {
         Ok(())
     }
 
+    #[instrument(skip_all)]
+    async fn add_component_fee_accruals(
+        &self,
+        accruals: &[ComponentFeeAccrual],
+    ) -> Result<(), StorageError> {
+        let mut conn =
+            self.pool.get().await.map_err(|e| {
+                StorageError::Unexpected(format!("Failed to retrieve connection: {e}"))
+            })?;
+        self.state_gateway
+            .add_component_fee_accruals(accruals, &self.chain, &mut conn)
+            .await
+    }
+
     #[instrument(skip_all)]
     async fn add_tokens(&self, tokens: &[Token]) -> Result<(), StorageError> {
         let mut conn =
@@ -597,6 +630,25 @@ impl ProtocolGateway for DirectGateway {
             .get_component_tvls(chain, system, ids, pagination_params, &mut conn)
             .await
     }
+
+    #[instrument(skip_all)]
+    async fn get_component_daily_fees(
+        &self,
+        chain: &Chain,
+        system: Option<String>,
+        ids: Option<&[&str]>,
+        start: NaiveDate,
+        end: NaiveDate,
+        pagination_params: Option<&PaginationParams>,
+    ) -> Result<WithTotal<Vec<ComponentDailyFees>>, StorageError> {
+        let mut conn =
+            self.pool.get().await.map_err(|e| {
+                StorageError::Unexpected(format!("Failed to retrieve connection: {e}"))
+            })?;
+        self.state_gateway
+            .get_component_daily_fees(chain, system, ids, start, end, pagination_params, &mut conn)
+            .await
+    }
 }
 
 #[async_trait]
@@ -1,8 +1,13 @@
 use diesel::{ExpressionMethods, QueryDsl};
-use diesel_async::{AsyncPgConnection, RunQueryDsl};
-use tycho_common::models::{Chain, ExtractionState};
+use diesel_async::{
+    scoped_futures::ScopedFutureExt, AsyncConnection, AsyncPgConnection, RunQueryDsl,
+};
+use tycho_common::{
+    models::{Chain, ExtractionState},
+    storage::BlockIdentifier,
+};
 
-use super::{orm, schema, storage_error_from_diesel, PostgresGateway, StorageError};
+use super::{orm, schema, storage_error_from_diesel, PostgresError, PostgresGateway, StorageError};
 
 impl PostgresGateway {
     pub async fn get_state(
@@ -88,6 +93,29 @@ impl PostgresGateway {
         }
         Ok(())
     }
+
+    /// Reverts the storage to `state.block_hash` and saves `state` in a single transaction.
+    ///
+    /// Either both or none of the changes are committed, so the stored cursor never points to a
+    /// reverted block and an interrupted revert leaves nothing to recover.
+    pub async fn revert_extraction_state(
+        &self,
+        state: &ExtractionState,
+        conn: &mut AsyncPgConnection,
+    ) -> Result<(), StorageError> {
+        conn.transaction(|conn| {
+            async move {
+                // The state is saved first, as it must not reference any of the deleted blocks.
+                self.save_state(state, conn).await?;
+                self.revert_state(&BlockIdentifier::Hash(state.block_hash.clone()), conn)
+                    .await?;
+                Result::<(), PostgresError>::Ok(())
+            }
+            .scope_boxed()
+        })
+        .await
+        .map_err(StorageError::from)
+    }
 }
 
 #[cfg(test)]
@@ -246,4 +274,43 @@ mod test {
             "20".to_owned().into_bytes()
         );
     }
+
+    const BLOCK_1: &str = "88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6";
+    const BLOCK_2: &str = "b495a1d7e6663152ae92708da4843337b958146015a2802f4193a410044698c9";
+
+    async fn block_exists(conn: &mut AsyncPgConnection, block_hash: &str) -> bool {
+        schema::block::table
+            .filter(schema::block::hash.eq(Bytes::from_str(block_hash).unwrap()))
+            .count()
+            .get_result::<i64>(conn)
+            .await
+            .unwrap() >
+            0
+    }
+
+    #[tokio::test]
+    async fn test_revert_extraction_state() {
+        let mut conn = setup_db().await;
+        let gateway = get_dgw(&mut conn).await;
+        let state = ExtractionState::new(
+            "setup_extractor".to_string(),
+            Chain::Ethereum,
+            None,
+            "5".as_bytes(),
+            Bytes::from_str(BLOCK_1).unwrap(),
+        );
+
+        gateway
+            .revert_extraction_state(&state, &mut conn)
+            .await
+            .unwrap();
+
+        let res = gateway
+            .get_state("setup_extractor", &Chain::Ethereum, &mut conn)
+            .await
+            .unwrap();
+        assert_eq!(res.cursor, "5".as_bytes());
+        assert_eq!(res.block_hash, Bytes::from_str(BLOCK_1).unwrap());
+        assert!(!block_exists(&mut conn, BLOCK_2).await);
+    }
 }
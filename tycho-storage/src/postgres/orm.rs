@@ -4,7 +4,7 @@ use async_trait::async_trait;
 use chrono::NaiveDateTime;
 use diesel::{
     dsl::{exists, sql},
-    pg::Pg,
+    pg::{data_types::PgNumeric, Pg},
     prelude::*,
     query_builder::{BoxedSqlQuery, SqlQuery},
     sql_query,
@@ -12,6 +12,7 @@ use diesel::{
 };
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use diesel_derive_enum::DbEnum;
+use num_bigint::BigUint;
 use tracing::trace;
 use tycho_common::{
     models::{
@@ -30,9 +31,9 @@ use tycho_common::{
 use super::{
     schema::{
         account, account_balance, block, chain, component_balance, component_balance_default,
-        component_tvl, contract_code, contract_storage, contract_storage_default,
-        debug_protocol_component_has_entry_point_tracing_params, entry_point,
-        entry_point_tracing_params, entry_point_tracing_params_calls_account,
+        component_fee_accrual, component_tvl, contract_code, contract_storage,
+        contract_storage_default, debug_protocol_component_has_entry_point_tracing_params,
+        entry_point, entry_point_tracing_params, entry_point_tracing_params_calls_account,
         entry_point_tracing_result, extraction_state, protocol_component,
         protocol_component_holds_contract, protocol_component_holds_token,
         protocol_component_uses_entry_point, protocol_state, protocol_state_default,
@@ -1801,6 +1802,69 @@ impl ComponentTVL {
     }
 }
 
+#[derive(Insertable, Debug)]
+#[diesel(table_name = component_fee_accrual)]
+pub struct NewComponentFeeAccrual {
+    pub protocol_component_id: i64,
+    pub token_id: i64,
+    pub block_id: i64,
+    pub ts: NaiveDateTime,
+    pub lp_fees: PgNumeric,
+    pub protocol_fees: PgNumeric,
+}
+
+/// Converts a big-endian encoded token amount to a `numeric(78, 0)` value.
+pub fn amount_to_numeric(amount: &Balance) -> PgNumeric {
+    let decimal = BigUint::from_bytes_be(amount).to_string();
+    // Postgres numerics are stored as base 10000 digits, most significant first.
+    let padded = format!("{}{}", "0".repeat((4 - decimal.len() % 4) % 4), decimal);
+    let mut digits: Vec<i16> = padded
+        .as_bytes()
+        .chunks(4)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .fold(0, |acc, digit| acc * 10 + i16::from(digit - b'0'))
+        })
+        .collect();
+    let weight = digits.len() as i16 - 1;
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+    PgNumeric::Positive { weight, scale: 0, digits }
+}
+
+/// Converts a `numeric(78, 0)` value to a 32 byte big-endian encoded token amount and its
+/// approximate float value.
+pub fn numeric_to_amount(value: &PgNumeric) -> Result<(Balance, f64), StorageError> {
+    let (weight, digits) = match value {
+        PgNumeric::Positive { weight, digits, .. } => (*weight, digits),
+        _ => {
+            return Err(StorageError::DecodeError(format!(
+                "Expected a non-negative amount, got {value:?}"
+            )))
+        }
+    };
+    let decimal = if digits.is_empty() {
+        "0".to_string()
+    } else if weight < 0 || digits.len() > weight as usize + 1 {
+        return Err(StorageError::DecodeError(format!(
+            "Expected an integral amount, got {value:?}"
+        )));
+    } else {
+        (1..=weight as usize).fold(digits[0].to_string(), |acc, idx| {
+            format!("{acc}{:04}", digits.get(idx).copied().unwrap_or(0))
+        })
+    };
+    let amount = decimal
+        .parse::<BigUint>()
+        .map_err(|err| StorageError::DecodeError(format!("Invalid amount {decimal}: {err}")))?;
+    let float = decimal
+        .parse::<f64>()
+        .map_err(|err| StorageError::DecodeError(format!("Invalid amount {decimal}: {err}")))?;
+    Ok((Bytes::from(amount.to_bytes_be()).lpad(32, 0), float))
+}
+
 #[derive(Debug, DbEnum, Clone, PartialEq, Eq, Hash)]
 #[ExistingTypePath = "crate::postgres::schema::sql_types::EntryPointTracingType"]
 pub enum EntryPointTracingType {
@@ -1,7 +1,8 @@
 use std::collections::{BTreeSet, HashMap, HashSet};
 
-use chrono::{NaiveDateTime, Utc};
+use chrono::{NaiveDate, NaiveDateTime, Utc};
 use diesel::{
+    pg::data_types::PgNumeric,
     prelude::*,
     upsert::{excluded, on_constraint},
 };
@@ -11,12 +12,12 @@ use tracing::{error, instrument, trace, warn, Level};
 use tycho_common::{
     models::{
         protocol::{
-            ComponentBalance, ProtocolComponent, ProtocolComponentState,
-            ProtocolComponentStateDelta, QualityRange,
+            ComponentBalance, ComponentDailyFees, ComponentFeeAccrual, ProtocolComponent,
+            ProtocolComponentState, ProtocolComponentStateDelta, QualityRange,
         },
         token::Token,
-        Address, Balance, Chain, ChangeType, ComponentId, FinancialType, ImplementationType,
-        PaginationParams, ProtocolType, StoreVal, TxHash,
+        Address, Balance, BlockHash, Chain, ChangeType, ComponentId, FinancialType,
+        ImplementationType, PaginationParams, ProtocolType, StoreVal, TxHash,
     },
     storage::{BlockOrTimestamp, StorageError, Version, WithTotal},
     Bytes,
@@ -1808,6 +1809,192 @@ impl PostgresGateway {
 
         Ok(WithTotal { entity: result, total: Some(count) })
     }
+
+    pub async fn add_component_fee_accruals(
+        &self,
+        accruals: &[ComponentFeeAccrual],
+        chain: &Chain,
+        conn: &mut AsyncPgConnection,
+    ) -> Result<(), StorageError> {
+        if accruals.is_empty() {
+            return Ok(());
+        }
+        let chain_db_id = self.get_chain_id(chain)?;
+
+        let token_addresses: Vec<&Address> = accruals
+            .iter()
+            .map(|accrual| &accrual.token)
+            .collect();
+        let token_ids: HashMap<Address, i64> = schema::token::table
+            .inner_join(schema::account::table)
+            .select((schema::account::address, schema::token::id))
+            .filter(schema::account::chain_id.eq(chain_db_id))
+            .filter(schema::account::address.eq_any(token_addresses))
+            .load::<(Address, i64)>(conn)
+            .await
+            .map_err(PostgresError::from)?
+            .into_iter()
+            .collect();
+
+        let block_hashes: Vec<&BlockHash> = accruals
+            .iter()
+            .map(|accrual| &accrual.block_hash)
+            .collect();
+        let block_ids_and_ts: HashMap<BlockHash, (i64, NaiveDateTime)> = schema::block::table
+            .select((schema::block::hash, schema::block::id, schema::block::ts))
+            .filter(schema::block::chain_id.eq(chain_db_id))
+            .filter(schema::block::hash.eq_any(block_hashes))
+            .load::<(BlockHash, i64, NaiveDateTime)>(conn)
+            .await
+            .map_err(PostgresError::from)?
+            .into_iter()
+            .map(|(hash, id, ts)| (hash, (id, ts)))
+            .collect();
+
+        let external_ids: Vec<&str> = accruals
+            .iter()
+            .map(|accrual| accrual.component_id.as_str())
+            .collect();
+        let protocol_component_ids: HashMap<String, i64> =
+            orm::ProtocolComponent::ids_by_external_ids(&external_ids, chain_db_id, conn)
+                .await
+                .map_err(PostgresError::from)?
+                .into_iter()
+                .map(|(component_id, external_id)| (external_id, component_id))
+                .collect();
+
+        let mut new_accruals = Vec::with_capacity(accruals.len());
+        for accrual in accruals.iter() {
+            let protocol_component_id = *protocol_component_ids
+                .get(&accrual.component_id)
+                .ok_or_else(|| {
+                    StorageError::NotFound(
+                        "ProtocolComponent".to_string(),
+                        accrual.component_id.clone(),
+                    )
+                })?;
+            let Some(&token_id) = token_ids.get(&accrual.token) else {
+                warn!(
+                    component_id = accrual.component_id,
+                    token = %accrual.token,
+                    "Skipping fee accrual of unknown token"
+                );
+                continue;
+            };
+            let (block_id, ts) = *block_ids_and_ts
+                .get(&accrual.block_hash)
+                .ok_or_else(|| {
+                    StorageError::NotFound("Block".to_string(), accrual.block_hash.to_string())
+                })?;
+            new_accruals.push(orm::NewComponentFeeAccrual {
+                protocol_component_id,
+                token_id,
+                block_id,
+                ts,
+                lp_fees: orm::amount_to_numeric(&accrual.lp_fees),
+                protocol_fees: orm::amount_to_numeric(&accrual.protocol_fees),
+            });
+        }
+
+        // Daily rollups are maintained by a trigger, ignoring conflicts avoids counting a block
+        // twice.
+        diesel::insert_into(schema::component_fee_accrual::table)
+            .values(&new_accruals)
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .await
+            .map_err(|err| storage_error_from_diesel(err, "ComponentFeeAccrual", "batch", None))?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_component_daily_fees(
+        &self,
+        chain: &Chain,
+        system: Option<String>,
+        component_ids: Option<&[&str]>,
+        start: NaiveDate,
+        end: NaiveDate,
+        pagination_params: Option<&PaginationParams>,
+        conn: &mut AsyncPgConnection,
+    ) -> Result<WithTotal<Vec<ComponentDailyFees>>, StorageError> {
+        use schema::{component_fee_daily::dsl as cfd, protocol_component::dsl as pc};
+
+        let chain_id_val = self.get_chain_id(chain)?;
+
+        let mut query = cfd::component_fee_daily
+            .inner_join(pc::protocol_component)
+            .inner_join(schema::token::table.inner_join(schema::account::table))
+            .filter(pc::chain_id.eq(chain_id_val))
+            .filter(cfd::day.between(start, end))
+            .into_boxed();
+
+        let mut count_query = cfd::component_fee_daily
+            .inner_join(pc::protocol_component)
+            .filter(pc::chain_id.eq(chain_id_val))
+            .filter(cfd::day.between(start, end))
+            .into_boxed();
+
+        if let Some(ids) = component_ids {
+            query = query.filter(pc::external_id.eq_any(ids));
+            count_query = count_query.filter(pc::external_id.eq_any(ids));
+        }
+
+        if let Some(system) = system {
+            let system_id = self.get_protocol_system_id(&system)?;
+            query = query.filter(pc::protocol_system_id.eq(system_id));
+            count_query = count_query.filter(pc::protocol_system_id.eq(system_id));
+        }
+
+        if let Some(pagination) = pagination_params {
+            query = query
+                .limit(pagination.page_size)
+                .offset(pagination.offset());
+        }
+
+        let count = count_query
+            .count()
+            .get_result::<i64>(conn)
+            .await
+            .map_err(PostgresError::from)?;
+
+        let rows: Vec<(String, Address, NaiveDate, PgNumeric, PgNumeric)> = query
+            .order_by((pc::external_id, schema::account::address, cfd::day))
+            .select((
+                pc::external_id,
+                schema::account::address,
+                cfd::day,
+                cfd::lp_fees,
+                cfd::protocol_fees,
+            ))
+            .load(conn)
+            .await
+            .map_err(|err| {
+                let id_hint = component_ids
+                    .and_then(|ids| ids.first().copied())
+                    .unwrap_or_default();
+                storage_error_from_diesel(err, "ComponentDailyFees", id_hint, None)
+            })?;
+
+        let result = rows
+            .into_iter()
+            .map(|(component_id, token, day, lp_fees, protocol_fees)| {
+                let (lp_fees, lp_fees_float) = orm::numeric_to_amount(&lp_fees)?;
+                let (protocol_fees, protocol_fees_float) = orm::numeric_to_amount(&protocol_fees)?;
+                Ok(ComponentDailyFees {
+                    component_id,
+                    token,
+                    day,
+                    lp_fees,
+                    lp_fees_float,
+                    protocol_fees,
+                    protocol_fees_float,
+                })
+            })
+            .collect::<Result<Vec<_>, StorageError>>()?;
+
+        Ok(WithTotal { entity: result, total: Some(count) })
+    }
 }
 
 #[cfg(test)]
@@ -4017,4 +4204,177 @@ mod test {
         assert_eq!(tvls.entity.get("state1"), Some(&2.0));
         assert!(!tvls.entity.contains_key("state3"));
     }
+
+    fn fee_accrual(
+        component_id: &str,
+        token: &str,
+        block_hash: &Bytes,
+        lp_fees: u128,
+        protocol_fees: u128,
+    ) -> ComponentFeeAccrual {
+        ComponentFeeAccrual::new(
+            component_id,
+            token.into(),
+            block_hash.clone(),
+            Bytes::from(lp_fees).lpad(32, 0),
+            Bytes::from(protocol_fees).lpad(32, 0),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_add_component_fee_accruals() {
+        let mut conn = setup_db().await;
+        setup_data(&mut conn).await;
+        let gw = EVMGateway::from_connection(&mut conn).await;
+        let block_1 =
+            Bytes::from("0x88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6");
+        let block_2 =
+            Bytes::from("0xb495a1d7e6663152ae92708da4843337b958146015a2802f4193a410044698c9");
+        // amounts exceeding the precision of a float
+        let accruals = [
+            fee_accrual("state1", WETH, &block_1, 100_000_000_000_000_000_001, 1),
+            fee_accrual("state1", WETH, &block_2, 200_000_000_000_000_000_002, 2),
+            fee_accrual("state1", USDC, &block_1, 10, 0),
+        ];
+
+        gw.add_component_fee_accruals(&accruals, &Chain::Ethereum, &mut conn)
+            .await
+            .expect("adding fee accruals failed");
+        // accruals of an already processed block are ignored
+        gw.add_component_fee_accruals(&accruals[..1], &Chain::Ethereum, &mut conn)
+            .await
+            .expect("adding duplicated fee accruals failed");
+
+        let day = db_fixtures::yesterday_midnight().date();
+        let res = gw
+            .get_component_daily_fees(
+                &Chain::Ethereum,
+                None,
+                Some(&["state1"]),
+                day,
+                day,
+                None,
+                &mut conn,
+            )
+            .await
+            .expect("failed retrieving daily fees");
+
+        assert_eq!(res.total, Some(2));
+        let weth = res
+            .entity
+            .iter()
+            .find(|fees| fees.token == Bytes::from(WETH))
+            .expect("missing WETH fees");
+        assert_eq!(weth.day, day);
+        assert_eq!(weth.lp_fees, Bytes::from(300_000_000_000_000_000_003u128).lpad(32, 0));
+        assert_eq!(weth.lp_fees_float, 300_000_000_000_000_000_003u128 as f64);
+        assert_eq!(weth.protocol_fees, Bytes::from(3u128).lpad(32, 0));
+        assert_eq!(weth.protocol_fees_float, 3.0);
+
+        // reverting a block removes its accruals from the rollup exactly
+        diesel::delete(schema::block::table.filter(schema::block::hash.eq(block_2)))
+            .execute(&mut conn)
+            .await
+            .expect("deleting block failed");
+        let res = gw
+            .get_component_daily_fees(&Chain::Ethereum, None, None, day, day, None, &mut conn)
+            .await
+            .expect("failed retrieving daily fees");
+
+        let weth = res
+            .entity
+            .iter()
+            .find(|fees| fees.token == Bytes::from(WETH))
+            .expect("missing WETH fees");
+        assert_eq!(weth.lp_fees, Bytes::from(100_000_000_000_000_000_001u128).lpad(32, 0));
+        assert_eq!(weth.protocol_fees, Bytes::from(1u128).lpad(32, 0));
+    }
+
+    #[tokio::test]
+    async fn test_add_component_fee_accruals_revert_component_creation() {
+        let mut conn = setup_db().await;
+        setup_data(&mut conn).await;
+        let gw = EVMGateway::from_connection(&mut conn).await;
+        let block_1 =
+            Bytes::from("0x88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6");
+        let block_2 =
+            Bytes::from("0xb495a1d7e6663152ae92708da4843337b958146015a2802f4193a410044698c9");
+        let accruals = [
+            fee_accrual("state1", WETH, &block_1, 1, 1),
+            fee_accrual("state1", WETH, &block_2, 2, 2),
+        ];
+        gw.add_component_fee_accruals(&accruals, &Chain::Ethereum, &mut conn)
+            .await
+            .expect("adding fee accruals failed");
+
+        // reverting the block that created the component deletes the component with its accruals
+        diesel::delete(schema::block::table.filter(schema::block::hash.eq(block_1)))
+            .execute(&mut conn)
+            .await
+            .expect("deleting block failed");
+
+        let day = db_fixtures::yesterday_midnight().date();
+        let res = gw
+            .get_component_daily_fees(&Chain::Ethereum, None, None, day, day, None, &mut conn)
+            .await
+            .expect("failed retrieving daily fees");
+        let n_accruals = schema::component_fee_accrual::table
+            .count()
+            .get_result::<i64>(&mut conn)
+            .await
+            .expect("failed counting fee accruals");
+        assert_eq!(res.total, Some(0));
+        assert!(res.entity.is_empty());
+        assert_eq!(n_accruals, 0);
+    }
+
+    #[tokio::test]
+    async fn test_add_component_fee_accruals_unknown_block() {
+        let mut conn = setup_db().await;
+        setup_data(&mut conn).await;
+        let gw = EVMGateway::from_connection(&mut conn).await;
+        let accrual = fee_accrual("state1", WETH, &Bytes::from("0x01"), 1, 0);
+
+        let res = gw
+            .add_component_fee_accruals(&[accrual], &Chain::Ethereum, &mut conn)
+            .await;
+
+        assert!(matches!(res, Err(StorageError::NotFound(entity, _)) if entity == "Block"));
+    }
+
+    #[tokio::test]
+    async fn test_add_component_fee_accruals_unknown_token() {
+        let mut conn = setup_db().await;
+        setup_data(&mut conn).await;
+        let gw = EVMGateway::from_connection(&mut conn).await;
+        let block_1 =
+            Bytes::from("0x88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6");
+        let max_amount = Bytes::from([0xff; 32]);
+        let accruals = [
+            ComponentFeeAccrual::new(
+                "state1",
+                WETH.into(),
+                block_1.clone(),
+                max_amount.clone(),
+                Bytes::from(0u8).lpad(32, 0),
+            ),
+            fee_accrual("state1", "0x0000000000000000000000000000000000000001", &block_1, 1, 1),
+        ];
+
+        gw.add_component_fee_accruals(&accruals, &Chain::Ethereum, &mut conn)
+            .await
+            .expect("adding fee accruals failed");
+
+        let day = db_fixtures::yesterday_midnight().date();
+        let res = gw
+            .get_component_daily_fees(&Chain::Ethereum, None, None, day, day, None, &mut conn)
+            .await
+            .expect("failed retrieving daily fees");
+        assert_eq!(res.total, Some(1));
+        assert_eq!(res.entity[0].token, Bytes::from(WETH));
+        assert_eq!(res.entity[0].lp_fees, max_amount);
+        assert_eq!(res.entity[0].lp_fees_float, 2f64.powi(256));
+        assert_eq!(res.entity[0].protocol_fees, Bytes::from(0u8).lpad(32, 0));
+        assert_eq!(res.entity[0].protocol_fees_float, 0.0);
+    }
 }
@@ -171,6 +171,31 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    component_fee_accrual (id) {
+        id -> Int8,
+        protocol_component_id -> Int8,
+        token_id -> Int8,
+        block_id -> Int8,
+        ts -> Timestamptz,
+        lp_fees -> Numeric,
+        protocol_fees -> Numeric,
+        inserted_ts -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    component_fee_daily (protocol_component_id, token_id, day) {
+        protocol_component_id -> Int8,
+        token_id -> Int8,
+        day -> Date,
+        lp_fees -> Numeric,
+        protocol_fees -> Numeric,
+        inserted_ts -> Timestamptz,
+        modified_ts -> Timestamptz,
+    }
+}
+
 diesel::table! {
     component_tvl (id) {
         id -> Int8,
@@ -374,6 +399,11 @@ diesel::joinable!(account_balance -> account (account_id));
 diesel::joinable!(account_balance -> token (token_id));
 diesel::joinable!(account_balance -> transaction (modify_tx));
 diesel::joinable!(block -> chain (chain_id));
+diesel::joinable!(component_fee_accrual -> block (block_id));
+diesel::joinable!(component_fee_accrual -> protocol_component (protocol_component_id));
+diesel::joinable!(component_fee_accrual -> token (token_id));
+diesel::joinable!(component_fee_daily -> protocol_component (protocol_component_id));
+diesel::joinable!(component_fee_daily -> token (token_id));
 diesel::joinable!(component_tvl -> protocol_component (protocol_component_id));
 diesel::joinable!(contract_code -> account (account_id));
 diesel::joinable!(contract_code -> transaction (modify_tx));
@@ -412,6 +442,8 @@ diesel::allow_tables_to_appear_in_same_query!(
     account_balance,
     block,
     chain,
+    component_fee_accrual,
+    component_fee_daily,
     component_tvl,
     contract_code,
     debug_protocol_component_has_entry_point_tracing_params,
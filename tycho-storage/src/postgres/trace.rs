@@ -0,0 +1,174 @@
+//! Traces of database writes.
+//!
+//! A [`WriteTrace`] records what the write executor did while persisting a single database
+//! transaction: which write operations were executed, how many rows each of them inserted,
+//! updated and deleted per table, how long they took and whether they failed. Traces are opt-in
+//! per transaction, see
+//! [`CachedGateway::start_traced_transaction`](super::cache::CachedGateway::start_traced_transaction).
+//!
+//! Diesel does not expose a hook to observe individual statements, so writes are traced at the
+//! granularity of write operations. Each operation corresponds to a single `PostgresGateway`
+//! method, e.g. `UpsertProtocolState` to `update_protocol_states`. The affected rows are read
+//! from postgres' statistics of the running transaction before and after each operation, so they
+//! include rows written by triggers. The SQL text of the statements and reads are not recorded.
+use std::{collections::BTreeMap, time::Duration};
+
+use diesel::{
+    sql_query,
+    sql_types::{BigInt, Text},
+    QueryableByName,
+};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::Serialize;
+use tycho_common::models::blockchain::Block;
+
+use super::PostgresError;
+
+/// Rows written to a single table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TableRows {
+    pub inserted: u64,
+    pub updated: u64,
+    pub deleted: u64,
+}
+
+impl TableRows {
+    pub fn total(&self) -> u64 {
+        self.inserted + self.updated + self.deleted
+    }
+}
+
+/// A single write operation executed within a traced transaction.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WriteOpTrace {
+    pub operation: String,
+    /// Number of entities passed to the operation.
+    pub entities: usize,
+    /// Rows the operation wrote, per table. `None` if they could not be read, which is the case
+    /// once an operation failed and aborted the transaction.
+    pub tables: Option<BTreeMap<String, TableRows>>,
+    pub duration: Duration,
+    /// Error returned by the operation. Duplicate entries are ignored by the write executor, so
+    /// an operation may report an error while the transaction still succeeds.
+    pub error: Option<String>,
+}
+
+impl WriteOpTrace {
+    /// Total number of rows the operation wrote, 0 if unknown.
+    pub fn rows(&self) -> u64 {
+        self.tables
+            .iter()
+            .flat_map(BTreeMap::values)
+            .map(TableRows::total)
+            .sum()
+    }
+}
+
+#[derive(QueryableByName)]
+struct TableRowsRow {
+    #[diesel(sql_type = Text)]
+    table_name: String,
+    #[diesel(sql_type = BigInt)]
+    inserted: i64,
+    #[diesel(sql_type = BigInt)]
+    updated: i64,
+    #[diesel(sql_type = BigInt)]
+    deleted: i64,
+}
+
+/// Rows written per table by the current transaction so far.
+pub(super) async fn transaction_table_rows(
+    conn: &mut AsyncPgConnection,
+) -> Result<BTreeMap<String, TableRows>, PostgresError> {
+    let rows: Vec<TableRowsRow> = sql_query(
+        r#"
+        SELECT relname::text AS table_name, n_tup_ins AS inserted, n_tup_upd AS updated,
+            n_tup_del AS deleted
+        FROM pg_stat_xact_user_tables
+        WHERE n_tup_ins + n_tup_upd + n_tup_del > 0
+        "#,
+    )
+    .load(conn)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            (
+                row.table_name,
+                TableRows {
+                    inserted: row.inserted as u64,
+                    updated: row.updated as u64,
+                    deleted: row.deleted as u64,
+                },
+            )
+        })
+        .collect())
+}
+
+/// Rows written between two reads of [`transaction_table_rows`].
+pub(super) fn table_rows_diff(
+    before: &BTreeMap<String, TableRows>,
+    after: BTreeMap<String, TableRows>,
+) -> BTreeMap<String, TableRows> {
+    after
+        .into_iter()
+        .filter_map(|(table, rows)| {
+            let prev = before
+                .get(&table)
+                .copied()
+                .unwrap_or_default();
+            let diff = TableRows {
+                inserted: rows
+                    .inserted
+                    .saturating_sub(prev.inserted),
+                updated: rows
+                    .updated
+                    .saturating_sub(prev.updated),
+                deleted: rows
+                    .deleted
+                    .saturating_sub(prev.deleted),
+            };
+            (diff.total() > 0).then_some((table, diff))
+        })
+        .collect()
+}
+
+/// Trace of a database transaction written by the write executor.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WriteTrace {
+    /// Name of the extractor that opened the transaction.
+    pub owner: Option<String>,
+    pub start_block: Block,
+    pub end_block: Block,
+    /// Number of times the transaction was attempted, transactions are retried on deadlocks.
+    pub attempts: usize,
+    /// Operations of the last attempt, in execution order.
+    pub operations: Vec<WriteOpTrace>,
+    /// Total time spent writing, including retries and the commit.
+    pub duration: Duration,
+    pub error: Option<String>,
+}
+
+impl WriteTrace {
+    /// Trace of a transaction that failed before it was submitted to the write executor, so
+    /// nothing was written.
+    pub fn unsubmitted(block: &Block, owner: Option<&str>, error: &impl ToString) -> Self {
+        Self {
+            owner: owner.map(String::from),
+            start_block: block.clone(),
+            end_block: block.clone(),
+            attempts: 0,
+            operations: Vec::new(),
+            duration: Duration::ZERO,
+            error: Some(error.to_string()),
+        }
+    }
+
+    /// Total number of rows written by all operations.
+    pub fn rows(&self) -> u64 {
+        self.operations
+            .iter()
+            .map(WriteOpTrace::rows)
+            .sum()
+    }
+}